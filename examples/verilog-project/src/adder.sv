@@ -0,0 +1,9 @@
+module adder(
+    input logic [7:0] a,
+    input logic [7:0] b,
+    output logic [7:0] sum,
+    output logic [7:0] doubled
+);
+    assign sum = a + b;
+    assign doubled = sum + sum;
+endmodule
@@ -0,0 +1,6 @@
+module ascending(
+    input[0:7] a,
+    output[0:7] b
+);
+    assign b = a;
+endmodule
@@ -0,0 +1,9 @@
+module clocks(
+    input fast_clk,
+    input slow_clk,
+    output logic[7:0] fast_count,
+    output logic[7:0] slow_count
+);
+    always_ff @(posedge fast_clk) fast_count <= fast_count + 1;
+    always_ff @(posedge slow_clk) slow_count <= slow_count + 1;
+endmodule
@@ -0,0 +1,3 @@
+// used by tests/file_list.rs
++incdir+src
+src/counter.sv
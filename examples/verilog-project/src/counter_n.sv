@@ -0,0 +1,10 @@
+module counter_n(
+    input clk,
+    input rst_n,
+    output logic[7:0] count
+);
+    always_ff @(posedge clk) begin
+        if (!rst_n) count <= 0;
+        else count <= count + 1;
+    end
+endmodule
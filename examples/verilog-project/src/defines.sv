@@ -0,0 +1,15 @@
+module defines (
+    input [7:0] a,
+`ifdef WITH_DEBUG
+    output [7:0] debug,
+`endif
+    output [7:0] b
+);
+`ifndef OFFSET
+    `define OFFSET 0
+`endif
+    assign b = a + `OFFSET;
+`ifdef WITH_DEBUG
+    assign debug = a;
+`endif
+endmodule
@@ -0,0 +1,8 @@
+import "DPI-C" function int unsigned checksum(input int unsigned data);
+
+module dpi_checksum(
+    input logic [31:0] data,
+    output logic [31:0] sum
+);
+    always_comb sum = checksum(data);
+endmodule
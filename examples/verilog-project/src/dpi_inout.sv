@@ -0,0 +1,13 @@
+import "DPI-C" function void bus_transfer(inout int unsigned data);
+
+module dpi_inout(
+    input logic [31:0] request,
+    output logic [31:0] response
+);
+    always_comb begin
+        int unsigned data;
+        data = request;
+        bus_transfer(data);
+        response = data;
+    end
+endmodule
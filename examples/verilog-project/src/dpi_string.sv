@@ -0,0 +1,9 @@
+import "DPI-C" function void log_message(input int unsigned level, input string message);
+
+module dpi_string(
+    input logic [31:0] level
+);
+    always_comb begin
+        log_message(level, $sformatf("level is %0d", level));
+    end
+endmodule
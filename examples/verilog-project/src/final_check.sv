@@ -0,0 +1,9 @@
+module final_check(
+    input logic ok
+);
+    final begin
+        if (!ok) begin
+            $error("final check failed");
+        end
+    end
+endmodule
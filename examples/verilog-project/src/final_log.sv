@@ -0,0 +1,12 @@
+module final_log(
+    input[7:0] id
+);
+    integer fd;
+
+    // records each run in a log named after `id`, so that tests can count them
+    final begin
+        fd = $fopen($sformatf("artifacts/final_log_%0d.txt", id), "a");
+        $fdisplay(fd, "final");
+        $fclose(fd);
+    end
+endmodule
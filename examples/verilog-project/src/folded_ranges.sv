@@ -0,0 +1,13 @@
+module folded_ranges #(
+    parameter WIDTH = 6
+) (
+    input [3+1:0] sum,
+    input [2*4-1:0] product,
+    input [(32>>1)-1:0] shifted,
+    input [-1+(1<<3):+0] negated,
+    input [24/2-1:0] quotient,
+    input [WIDTH-1:0] parameterized,
+    output [4:0] out
+);
+    assign out = sum;
+endmodule
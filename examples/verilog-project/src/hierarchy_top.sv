@@ -0,0 +1,15 @@
+module hierarchy_leaf(
+    input clk,
+    output logic[3:0] value
+);
+    logic[3:0] state /*verilator public*/;
+    always_ff @(posedge clk) state <= state + 1;
+    assign value = state;
+endmodule
+
+module hierarchy_top(
+    input clk,
+    output logic[3:0] value
+);
+    hierarchy_leaf leaf(.clk(clk), .value(value));
+endmodule
@@ -0,0 +1,6 @@
+module inout_bus(
+    inout logic [7:0] bus,
+    output logic [7:0] echoed
+);
+    assign echoed = bus + 1;
+endmodule
@@ -48,3 +48,115 @@ mod parses_var_test {
             == size_of::<types::CData>()
     );
 }
+
+/// Compiles if `workspace = true` resolves `src` against the workspace root.
+pub mod workspace_relative_test {
+    use marlin::verilog::prelude::*;
+
+    #[verilog(
+        src = "examples/verilog-project/src/main.sv",
+        name = "main",
+        workspace = true
+    )]
+    pub struct WorkspaceRelativeMain;
+}
+
+/// Compiles if ports with ascending ranges like `[0:7]` are accepted.
+pub mod ascending_range_test {
+    use marlin::{verilator::types, verilog::prelude::*};
+
+    #[verilog(src = "src/ascending.sv", name = "ascending")]
+    pub struct Ascending;
+
+    const _: fn(Ascending) -> types::CData = |dut| dut.a;
+}
+
+/// A model whose `SCALE` parameter is selected by a const generic.
+#[verilog(src = "src/scaled.sv", name = "scaled", param("SCALE") = N)]
+pub struct Scaled<const N: usize>;
+
+#[verilog(src = "src/signed.sv", name = "signed_sample")]
+pub struct SignedSample;
+
+#[verilog(src = "src/final_check.sv", name = "final_check")]
+pub struct FinalCheck;
+
+#[verilog(src = "src/final_log.sv", name = "final_log")]
+pub struct FinalLog;
+
+#[verilog(src = "src/finisher.sv", name = "finisher", clock = "clk")]
+pub struct Finisher;
+
+#[verilog(
+    src = "src/counter.sv",
+    name = "counter",
+    clock = "clk",
+    reset = "rst"
+)]
+pub struct Counter;
+
+#[verilog(src = "src/net_types.sv", name = "net_types")]
+pub struct NetTypes;
+
+/// A model whose input starts out nonzero.
+#[verilog(src = "src/main.sv", name = "main", init("medium_input") = 7)]
+pub struct IdleMain;
+
+#[verilog(src = "src/dpi_inout.sv", name = "dpi_inout")]
+pub struct DpiInout;
+
+#[verilog(src = "src/dpi_checksum.sv", name = "dpi_checksum")]
+pub struct DpiChecksum;
+
+#[verilog(src = "src/dpi_string.sv", name = "dpi_string")]
+pub struct DpiString;
+
+/// A model of a module with a port Marlin cannot bind, which is skipped.
+#[allow(deprecated)]
+mod partial_ports {
+    use marlin::verilog::prelude::*;
+
+    #[verilog(
+        src = "src/partial_ports.sv",
+        name = "partial_ports",
+        ignore_unsupported_ports = true
+    )]
+    pub struct PartialPorts;
+}
+
+pub use partial_ports::PartialPorts;
+
+#[verilog(src = "src/adder.sv", name = "adder", combinational = true)]
+pub struct Adder;
+
+#[verilog(src = "src/inout_bus.sv", name = "inout_bus")]
+pub struct InoutBus;
+
+#[verilog(
+    src = "src/counter_n.sv",
+    name = "counter_n",
+    clock = "clk",
+    reset = "rst_n",
+    reset_active_low
+)]
+pub struct CounterN;
+
+#[verilog(src = "src/radix_ranges.sv", name = "radix_ranges")]
+pub struct RadixRanges;
+
+#[verilog(src = "src/folded_ranges.sv", name = "folded_ranges")]
+pub struct FoldedRanges;
+
+#[verilog(
+    src = "src/folded_ranges.sv",
+    name = "folded_ranges",
+    param("WIDTH") = 10
+)]
+pub struct WideFoldedRanges;
+
+#[verilog(
+    src = "src/defines.sv",
+    name = "defines",
+    define(WITH_DEBUG, OFFSET = 3)
+)]
+pub struct Defines;
@@ -0,0 +1,22 @@
+module first_listed(
+    input a,
+    output b
+);
+    assign b = a;
+endmodule
+
+// non-ANSI headers are not listed, since Marlin cannot parse their ports
+module non_ansi(a, b);
+    input a;
+    output b;
+    assign b = a;
+endmodule
+
+module second_listed #(
+    parameter int WIDTH = 4
+) (
+    input [WIDTH-1:0] a,
+    output [WIDTH-1:0] b
+);
+    assign b = a;
+endmodule
@@ -0,0 +1,7 @@
+module net_types(
+    input tri [7:0] a,
+    input wand [7:0] b,
+    output wor [7:0] c
+);
+    assign c = a & b;
+endmodule
@@ -0,0 +1,7 @@
+module partial_ports(
+    input logic [7:0] a,
+    input int b,
+    output logic [7:0] c
+);
+    assign c = a + b[7:0];
+endmodule
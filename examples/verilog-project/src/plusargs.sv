@@ -0,0 +1,11 @@
+module plusargs(
+    output logic [31:0] seed,
+    output logic verbose
+);
+    initial begin
+        if (!$value$plusargs("SEED=%d", seed)) begin
+            seed = 0;
+        end
+        verbose = $test$plusargs("VERBOSE") != 0;
+    end
+endmodule
@@ -0,0 +1,8 @@
+module quiet_display(
+    input clk,
+    input[7:0] value
+);
+    always_ff @(posedge clk) begin
+        $display("quiet_display value=%0d", value);
+    end
+endmodule
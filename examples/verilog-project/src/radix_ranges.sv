@@ -0,0 +1,10 @@
+module radix_ranges(
+    input [8'hF:0] sized_hex,
+    input ['h1F:0] hex,
+    input [3'b111:0] binary,
+    input ['o17:0] octal,
+    input ['d9:0] based_decimal,
+    output [7:0] out
+);
+    assign out = binary;
+endmodule
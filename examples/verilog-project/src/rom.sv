@@ -0,0 +1,10 @@
+module rom(
+    input[1:0] addr,
+    output[7:0] data
+);
+    logic [7:0] memory [0:3];
+
+    initial $readmemh("rom.hex", memory);
+
+    assign data = memory[addr];
+endmodule
@@ -0,0 +1,8 @@
+module scaled #(
+    parameter int SCALE = 1
+) (
+    input [7:0] a,
+    output [15:0] b
+);
+    assign b = a * SCALE;
+endmodule
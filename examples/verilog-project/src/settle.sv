@@ -0,0 +1,8 @@
+module settle(
+    input clk,
+    input rst,
+    input logic [7:0] data,
+    output logic [7:0] out
+);
+    assign out = rst ? 0 : data;
+endmodule
@@ -0,0 +1,6 @@
+module signed_sample(
+    input[11:0] raw,
+    output logic signed[11:0] sample
+);
+    assign sample = raw;
+endmodule
@@ -0,0 +1,6 @@
+module wide(
+    input logic [99:0] a,
+    output logic [99:0] b
+);
+    assign b = a + 1;
+endmodule
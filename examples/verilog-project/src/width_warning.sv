@@ -0,0 +1,7 @@
+module width_warning (
+    input [7:0] a,
+    output [3:0] b
+);
+    // truncates `a`, which Verilator reports as a WIDTH warning
+    assign b = a;
+endmodule
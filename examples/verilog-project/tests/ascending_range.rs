@@ -0,0 +1,47 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn ascending_ranges_bind() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/ascending.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut ascending = runtime.create_dyn_model(
+        "ascending",
+        "src/ascending.sv",
+        &[
+            ("a", 0, 7, PortDirection::Input),
+            ("b", 0, 7, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    ascending.pin("a", 0xa5u8).whatever_context("pin")?;
+    ascending.eval();
+    assert_eq!(ascending.read("b").whatever_context("read")?, 0xa5u8.into());
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::thread;
+
+use example_verilog_project::Main;
+use marlin::verilator::AssertSend;
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn model_runs_on_worker_thread() -> Result<(), Whatever> {
+    let main = marlin::quick_model::<Main>(&["src/main.sv"])?;
+
+    // SAFETY: the runtime is leaked and not used until the worker finishes
+    let mut main = unsafe { AssertSend::new(main) };
+    let main = thread::spawn(move || {
+        main.medium_input = 5;
+        main.eval();
+        main
+    })
+    .join()
+    .expect("worker thread panicked");
+
+    assert_eq!(main.into_inner().medium_output, 5);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Main;
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+    bench::{BenchHarness, bench_eval},
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn benchmarks_run() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref(), "src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+    bench_eval(&mut main, 1000);
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    counter.set_clock_port("clk").whatever_context("clock")?;
+
+    // a tiny xorshift generator stands in for a seeded RNG
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut harness = BenchHarness::new(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+    .warmup_iterations(100);
+    harness
+        .run(&mut counter, 1000)
+        .whatever_context("Failed to run benchmark")?;
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ErrorCompat, ResultExt, Whatever};
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("a", 7, 0, PortDirection::Input),
+    ("b", 3, 0, PortDirection::Output),
+];
+
+fn runtime(
+    treat_warnings_as_errors: bool,
+) -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/width_warning.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            // warnings are only collected from builds that invoke Verilator
+            force_verilator_rebuild: true,
+            treat_warnings_as_errors,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+}
+
+#[test]
+#[snafu::report]
+fn collects_warnings_without_failing() -> Result<(), Whatever> {
+    let runtime = runtime(false)?;
+
+    let mut model = runtime.create_dyn_model(
+        "width_warning",
+        "src/width_warning.sv",
+        PORTS,
+        VerilatedModelConfig::default(),
+    )?;
+    model.pin("a", 0x5au8).whatever_context("pin")?;
+    model.eval();
+    assert_eq!(model.read("b").whatever_context("read")?, 0xau8.into());
+
+    let warnings = runtime.build_warnings();
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.starts_with("%Warning-WIDTH")),
+        "expected a WIDTH warning, got {warnings:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn treats_warnings_as_errors() -> Result<(), Whatever> {
+    let runtime = runtime(true)?;
+
+    let Err(error) = runtime.create_dyn_model(
+        "width_warning",
+        "src/width_warning.sv",
+        PORTS,
+        VerilatedModelConfig::default(),
+    ) else {
+        panic!("the build should fail on the WIDTH warning");
+    };
+    // the warnings are reported by the innermost error
+    let message = error
+        .iter_chain()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(message.contains("treated as errors"), "{message}");
+    assert!(message.contains("%Warning-WIDTH"), "{message}");
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::Whatever;
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("medium_input", 31, 0, PortDirection::Input),
+    ("medium_output", 31, 0, PortDirection::Output),
+];
+
+#[test]
+fn every_field_affects_cache_key() {
+    let config = VerilatedModelConfig::default();
+    assert!(config.shares_library_with(&config.clone(), PORTS));
+
+    let quiet = VerilatedModelConfig {
+        quiet: true,
+        ..Default::default()
+    };
+    assert!(!config.shares_library_with(&quiet, PORTS));
+
+    let with_assertions = VerilatedModelConfig {
+        extra_verilator_args: vec!["--assert".into()],
+        ..Default::default()
+    };
+    assert!(!config.shares_library_with(&with_assertions, PORTS));
+    assert_ne!(
+        config.cache_key_with(PORTS),
+        config.cache_key_with(&PORTS[..1])
+    );
+}
+
+#[test]
+#[snafu::report]
+fn cache_key_names_artifact_directory() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let config = VerilatedModelConfig::default();
+    let _main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        PORTS,
+        config.clone(),
+    )?;
+
+    let library_path = runtime
+        .library_path_for("main", "src/main.sv", PORTS, &config)
+        .expect("library was just built");
+    let artifact_directory = library_path
+        .parent()
+        .and_then(|obj_dir| obj_dir.parent())
+        .and_then(|directory| directory.file_name())
+        .expect("library is in a subdirectory of the artifacts");
+    assert!(
+        artifact_directory
+            .ends_with(&format!("_{}", config.cache_key_with(PORTS)))
+    );
+
+    Ok(())
+}
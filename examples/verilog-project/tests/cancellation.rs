@@ -0,0 +1,71 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{sync::atomic::Ordering, thread, time::Duration};
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    dynamic::DynamicVerilatedModelError,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn run_loops_can_be_cancelled() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    counter.set_reset_port("rst").whatever_context("reset")?;
+    counter.reset().whatever_context("reset")?;
+
+    counter.run_cycles(3).whatever_context("run_cycles")?;
+    let cycles = counter
+        .run_until(|counter| Ok(counter.read("count")? == 5u8.into()))
+        .whatever_context("run_until")?;
+    assert_eq!(cycles, 2);
+
+    let cancelled = counter.cancellation_flag();
+    let watchdog = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        cancelled.store(true, Ordering::Relaxed);
+    });
+    let result = counter.run_until(|_| Ok(false));
+    watchdog.join().expect("watchdog panicked");
+    assert!(matches!(
+        result,
+        Err(DynamicVerilatedModelError::Cancelled { .. })
+    ));
+
+    counter.cancellation_flag().store(false, Ordering::Relaxed);
+    counter.run_cycles(1).whatever_context("run_cycles")?;
+
+    Ok(())
+}
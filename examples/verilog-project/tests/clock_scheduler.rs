@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    clock::{ClockScheduler, ClockSpec},
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn drives_independent_clocks() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/clocks.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut clocks = runtime.create_dyn_model(
+        "clocks",
+        "src/clocks.sv",
+        &[
+            ("fast_clk", 0, 0, PortDirection::Input),
+            ("slow_clk", 0, 0, PortDirection::Input),
+            ("fast_count", 7, 0, PortDirection::Output),
+            ("slow_count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    let mut scheduler = ClockScheduler::new([
+        ClockSpec {
+            port: "fast_clk".into(),
+            period_ps: 1_000,
+            phase_ps: 0,
+        },
+        ClockSpec {
+            port: "slow_clk".into(),
+            period_ps: 3_000,
+            phase_ps: 500,
+        },
+    ])?;
+
+    scheduler
+        .advance(&mut clocks, 10_000)
+        .whatever_context("advance")?;
+    assert_eq!(scheduler.time_ps(), 10_000);
+
+    // rising edges at 0, 1000, ..., 9000 and 500, 3500, 6500, 9500
+    assert_eq!(
+        clocks.read("fast_count").whatever_context("read")?,
+        10u8.into()
+    );
+    assert_eq!(
+        clocks.read("slow_count").whatever_context("read")?,
+        4u8.into()
+    );
+
+    Ok(())
+}
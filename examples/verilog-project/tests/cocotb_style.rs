@@ -0,0 +1,60 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions, dut::Dut,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn cocotb_style_counter() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    let mut dut = Dut::new(&mut counter);
+
+    dut.set("rst", 1u8).whatever_context("set rst")?;
+    dut.rising_edge("clk").whatever_context("clock")?;
+    assert_eq!(dut.get("count").whatever_context("get count")?, 0u8.into());
+
+    dut.set("rst", 0u8).whatever_context("set rst")?;
+    for expected in 1..=3u8 {
+        dut.rising_edge("clk").whatever_context("clock")?;
+        assert_eq!(
+            dut.get("count").whatever_context("get count")?,
+            expected.into()
+        );
+    }
+
+    assert!(dut.set("count", 0u8).is_err());
+    assert!(dut.rising_edge("nonexistent").is_err());
+
+    Ok(())
+}
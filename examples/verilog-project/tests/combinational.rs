@@ -0,0 +1,38 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Adder;
+use marlin::verilator::{VerilatorRuntime, VerilatorRuntimeOptions};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn evaluate_settles_without_clock() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/adder.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut adder = runtime.create_model_simple::<Adder>()?;
+
+    adder.a = 3;
+    adder.b = 4;
+    adder.evaluate()?;
+    assert_eq!(adder.sum, 7);
+    assert_eq!(adder.doubled, 14);
+
+    Ok(())
+}
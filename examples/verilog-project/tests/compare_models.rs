@@ -0,0 +1,191 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Main;
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+    compare::{
+        Divergence, Expected, TestVector, compare_models,
+        compare_models_masked, run_vectors,
+    },
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn static_and_dynamic_models_agree() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+    let mut dynamic_main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    compare_models(
+        &mut main,
+        &mut dynamic_main,
+        |cycle| vec![("medium_input", (cycle as u32 * 7919).into())],
+        &["medium_output"],
+        100,
+    )
+    .whatever_context("Models diverged")?;
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn test_vectors_skip_dont_cares() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+
+    run_vectors(
+        &mut main,
+        [
+            TestVector {
+                inputs: vec![("medium_input", 5u32.into())],
+                outputs: vec![("medium_output", Expected::Exact(5u32.into()))],
+            },
+            TestVector {
+                inputs: vec![("medium_input", 0x1234u32.into())],
+                outputs: vec![(
+                    "medium_output",
+                    Expected::Masked {
+                        value: 0xff34u32.into(),
+                        mask: 0xff,
+                    },
+                )],
+            },
+            TestVector {
+                inputs: vec![("medium_input", 9u32.into())],
+                outputs: vec![("medium_output", Expected::DontCare)],
+            },
+        ],
+    )
+    .whatever_context("Test vectors failed")?;
+
+    let failure = run_vectors(
+        &mut main,
+        [TestVector {
+            inputs: vec![("medium_input", 1u32.into())],
+            outputs: vec![("medium_output", Expected::Exact(2u32.into()))],
+        }],
+    );
+    assert!(failure.is_err());
+
+    // only the masked bits are compared, so a difference in bit 8 fails
+    let failure = run_vectors(
+        &mut main,
+        [TestVector {
+            inputs: vec![("medium_input", 0x100u32.into())],
+            outputs: vec![(
+                "medium_output",
+                Expected::Masked {
+                    value: 0u32.into(),
+                    mask: 0x1ff,
+                },
+            )],
+        }],
+    );
+    assert!(failure.is_err());
+
+    Ok(())
+}
+
+/// Like `main`, but flips bit 8 of the output once the input reaches 3.
+const DIVERGING_MAIN: &str = "
+module diverging_main(
+    input[31:0] medium_input,
+    output[31:0] medium_output
+);
+    assign medium_output =
+        medium_input >= 3 ? medium_input ^ 32'h100 : medium_input;
+endmodule
+";
+
+#[test]
+#[snafu::report]
+fn reports_first_divergence() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let ports = [
+        ("medium_input", 31, 0, PortDirection::Input),
+        ("medium_output", 31, 0, PortDirection::Output),
+    ];
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &ports,
+        VerilatedModelConfig::default(),
+    )?;
+    let mut diverging_main = runtime.create_dyn_model_from_source(
+        "diverging_main",
+        DIVERGING_MAIN,
+        &ports,
+        VerilatedModelConfig::default(),
+    )?;
+
+    let divergence = compare_models(
+        &mut main,
+        &mut diverging_main,
+        |cycle| vec![("medium_input", (cycle as u32).into())],
+        &["medium_output"],
+        10,
+    );
+    let Err(Divergence::Mismatch { cycle, port, a, b }) = divergence else {
+        panic!("expected the models to diverge, got {divergence:?}");
+    };
+    assert_eq!(cycle, 3);
+    assert_eq!(port, "medium_output");
+    assert_eq!(a, 3u32.into());
+    assert_eq!(b, 0x103u32.into());
+
+    // bit 8 is a don't-care under this mask
+    compare_models_masked(
+        &mut main,
+        &mut diverging_main,
+        |cycle| vec![("medium_input", (cycle as u32).into())],
+        &[("medium_output", 0xff)],
+        10,
+    )
+    .whatever_context("Models diverged in the masked bits")?;
+
+    Ok(())
+}
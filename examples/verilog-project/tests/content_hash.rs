@@ -0,0 +1,105 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fs::{self, File},
+    time::{Duration, SystemTime},
+};
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+const SOURCE_PATH: &str = "artifacts_content_hash/relay.sv";
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("a", 7, 0, PortDirection::Input),
+    ("b", 7, 0, PortDirection::Output),
+];
+
+/// Builds (if needed) the relay module, checks that it adds `offset`, and
+/// returns when its library was last written.
+fn build(offset: u8) -> Result<SystemTime, Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts_content_hash".into(),
+        &[SOURCE_PATH.as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut relay = runtime.create_dyn_model(
+        "relay",
+        SOURCE_PATH,
+        PORTS,
+        VerilatedModelConfig::default(),
+    )?;
+    relay.pin("a", 1u8).whatever_context("pin")?;
+    relay.eval();
+    assert_eq!(
+        relay.read("b").whatever_context("read")?,
+        (1 + offset).into()
+    );
+
+    let library_path = runtime
+        .library_path_for(
+            "relay",
+            SOURCE_PATH,
+            PORTS,
+            &VerilatedModelConfig::default(),
+        )
+        .expect("library was just built");
+    fs::metadata(library_path)
+        .and_then(|metadata| metadata.modified())
+        .whatever_context("Failed to read library metadata")
+}
+
+fn write_source(offset: u8) -> Result<(), Whatever> {
+    fs::write(
+        SOURCE_PATH,
+        format!(
+            "module relay(input[7:0] a, output[7:0] b); assign b = a + {offset}; endmodule\n"
+        ),
+    )
+    .whatever_context("Failed to write source")
+}
+
+#[test]
+#[snafu::report]
+fn rebuilds_only_when_contents_change() -> Result<(), Whatever> {
+    fs::create_dir_all("artifacts_content_hash")
+        .whatever_context("Failed to create artifact directory")?;
+    write_source(0)?;
+    let built_at = build(0)?;
+
+    // as if `git checkout` touched the file without editing it
+    File::options()
+        .write(true)
+        .open(SOURCE_PATH)
+        .and_then(|file| {
+            file.set_modified(SystemTime::now() + Duration::from_secs(60))
+        })
+        .whatever_context("Failed to touch source")?;
+    assert_eq!(
+        build(0)?,
+        built_at,
+        "Touching the source rebuilt the library"
+    );
+
+    write_source(1)?;
+    assert_ne!(build(1)?, built_at, "Editing the source did not rebuild");
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Defines;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn macro_defines_select_ports_and_logic() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/defines.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut model = runtime.create_model_simple::<Defines>()?;
+    model.a = 10;
+    model.eval();
+
+    assert_eq!(model.b, 13);
+    assert_eq!(model.debug, 10);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn config_defines_reach_verilator() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/defines.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let config = VerilatedModelConfig {
+        defines: vec![("OFFSET".into(), Some("5".into()))],
+        ..Default::default()
+    };
+    let ports = [
+        ("a", 7, 0, PortDirection::Input),
+        ("b", 7, 0, PortDirection::Output),
+    ];
+    let build_config = runtime.effective_build_config(
+        "defines",
+        "src/defines.sv",
+        &ports,
+        &config,
+    )?;
+    assert_eq!(build_config.defines, config.defines);
+    assert!(
+        build_config
+            .verilator_arguments
+            .contains(&"+define+OFFSET=5".into())
+    );
+
+    let mut model = runtime.create_dyn_model(
+        "defines",
+        "src/defines.sv",
+        &ports,
+        config,
+    )?;
+    model.pin("a", 10u8).whatever_context("pin")?;
+    model.eval();
+    assert_eq!(model.read("b").whatever_context("read")?, 15u8.into());
+
+    Ok(())
+}
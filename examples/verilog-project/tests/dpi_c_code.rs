@@ -0,0 +1,60 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::{
+    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilog::prelude::*,
+};
+use snafu::Whatever;
+
+#[verilog::dpi]
+pub extern "C" fn set_out(output: &mut i32) {
+    *output = 3;
+}
+
+#[test]
+#[snafu::report]
+fn shows_dpi_c_code() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/dpi.sv".as_ref()],
+        &[],
+        [set_out],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let c_code = runtime
+        .dpi_c_code("dpi_main")
+        .expect("runtime has DPI functions");
+    assert!(c_code.contains("#include \"Vdpi_main__Dpi.h\""));
+    assert!(c_code.contains("void set_out(int32_t* output)"));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn no_dpi_c_code_without_dpi_functions() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    assert!(runtime.dpi_c_code("main").is_none());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::DpiChecksum;
+use marlin::{
+    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilog::prelude::*,
+};
+use snafu::Whatever;
+
+#[verilog::dpi]
+pub extern "C" fn checksum(data: u32) -> u32 {
+    !data
+}
+
+/// Never compiled into the library, since its guard is always false.
+#[verilog::dpi(guard = "0")]
+pub extern "C" fn guarded_out(output: &mut u32) {
+    *output = 1;
+}
+
+#[test]
+#[snafu::report]
+fn false_guard_leaves_function_unbound() -> Result<(), Whatever> {
+    // the library differs from the one built without `guarded_out`, so it gets
+    // its own artifact directory
+    let runtime = VerilatorRuntime::new(
+        "artifacts_dpi_guard".into(),
+        &["src/dpi_checksum.sv".as_ref()],
+        &[],
+        [checksum, guarded_out],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let c_code = runtime
+        .dpi_c_code("dpi_checksum")
+        .expect("runtime has DPI functions");
+    assert!(c_code.contains("#if 0\nstatic void (*rust_guarded_out)"));
+    // the name is still recognized so that loading the library succeeds
+    assert!(c_code.contains("strcmp(name, \"guarded_out\")"));
+
+    let mut dpi_checksum = runtime.create_model_simple::<DpiChecksum>()?;
+    dpi_checksum.data = 5;
+    dpi_checksum.eval();
+    assert_eq!(dpi_checksum.sum, !5);
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::DpiInout;
+use marlin::{
+    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilog::prelude::*,
+};
+use snafu::Whatever;
+
+/// Responds to the incoming value by overwriting it.
+#[verilog::dpi]
+pub extern "C" fn bus_transfer(data: &mut u32) {
+    *data = *data * 2 + 1;
+}
+
+#[test]
+#[snafu::report]
+fn inout_argument_is_observed_and_mutated() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/dpi_inout.sv".as_ref()],
+        &[],
+        [bus_transfer],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut dpi_inout = runtime.create_model_simple::<DpiInout>()?;
+
+    dpi_inout.request = 20;
+    dpi_inout.eval();
+    assert_eq!(dpi_inout.response, 41);
+
+    dpi_inout.request = 7;
+    dpi_inout.eval();
+    assert_eq!(dpi_inout.response, 15);
+
+    Ok(())
+}
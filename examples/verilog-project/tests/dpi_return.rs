@@ -0,0 +1,54 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::DpiChecksum;
+use marlin::{
+    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilog::prelude::*,
+};
+use snafu::Whatever;
+
+/// Folds the bytes of `data` into a checksum.
+fn fold_bytes(data: u32) -> u32 {
+    data.to_le_bytes()
+        .iter()
+        .fold(0, |sum: u32, byte| sum.rotate_left(5) ^ u32::from(*byte))
+}
+
+#[verilog::dpi]
+pub extern "C" fn checksum(data: u32) -> u32 {
+    fold_bytes(data)
+}
+
+#[test]
+#[snafu::report]
+fn return_value_reaches_verilog() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/dpi_checksum.sv".as_ref()],
+        &[],
+        [checksum],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut dpi_checksum = runtime.create_model_simple::<DpiChecksum>()?;
+
+    for data in [0, 1, 0xdead_beef] {
+        dpi_checksum.data = data;
+        dpi_checksum.eval();
+        assert_eq!(dpi_checksum.sum, fold_bytes(data));
+    }
+
+    Ok(())
+}
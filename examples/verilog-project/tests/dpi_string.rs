@@ -0,0 +1,53 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+
+use example_verilog_project::DpiString;
+use marlin::{
+    verilator::{VerilatorRuntime, VerilatorRuntimeOptions},
+    verilog::prelude::*,
+};
+use snafu::Whatever;
+
+static MESSAGES: Mutex<Vec<(u32, String)>> = Mutex::new(vec![]);
+
+#[verilog::dpi]
+pub extern "C" fn log_message(level: u32, message: &str) {
+    MESSAGES.lock().unwrap().push((level, message.to_string()));
+}
+
+#[test]
+#[snafu::report]
+fn string_argument_reaches_rust() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/dpi_string.sv".as_ref()],
+        &[],
+        [log_message],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut dpi_string = runtime.create_model_simple::<DpiString>()?;
+
+    dpi_string.level = 3;
+    dpi_string.eval();
+
+    assert_eq!(
+        MESSAGES.lock().unwrap().last(),
+        Some(&(3, "level is 3".to_string()))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn dynamic_tick_and_reset() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    assert_eq!(counter.undriven_inputs(), ["clk", "rst"]);
+    assert!(counter.tick().is_err(), "We didn't set the clock port");
+    assert!(counter.set_clock_port("count").is_err());
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    counter.set_reset_port("rst").whatever_context("reset")?;
+    assert!(
+        counter
+            .set_post_reset_inputs([("count", 1u8.into())])
+            .is_err(),
+        "Post-reset inputs must be inputs"
+    );
+
+    counter.reset().whatever_context("reset")?;
+    assert_eq!(counter.read("count").whatever_context("read")?, 0u8.into());
+    assert!(counter.undriven_inputs().is_empty());
+
+    for _ in 0..3 {
+        counter.tick().whatever_context("tick")?;
+    }
+    assert_eq!(counter.read("count").whatever_context("read")?, 3u8.into());
+
+    counter.reset().whatever_context("reset")?;
+    assert_eq!(counter.read("count").whatever_context("read")?, 0u8.into());
+
+    counter.tick().whatever_context("tick")?;
+    counter.pulse("rst", 2).whatever_context("pulse")?;
+    assert_eq!(counter.read("count").whatever_context("read")?, 0u8.into());
+    counter.tick().whatever_context("tick")?;
+    assert_eq!(counter.read("count").whatever_context("read")?, 1u8.into());
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn reports_resolved_build_config() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &["src".as_ref()],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let config = VerilatedModelConfig {
+        verilator_optimization: 2,
+        parameters: vec![("WIDTH".into(), "8".into())],
+        extra_verilator_args: vec!["--x-assign".into(), "unique".into()],
+        ..Default::default()
+    };
+    let build_config = runtime.effective_build_config(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        &config,
+    )?;
+
+    assert!(build_config.verilator_version.starts_with("Verilator "));
+    assert_eq!(build_config.source_files, ["src/main.sv"]);
+    assert_eq!(build_config.include_directories, ["src"]);
+    assert_eq!(build_config.parameters, config.parameters);
+    for argument in ["--top-module", "main", "src/main.sv", "-Isrc", "-O2"] {
+        assert!(
+            build_config
+                .verilator_arguments
+                .iter()
+                .any(|other| other == argument),
+            "missing Verilator argument {argument}"
+        );
+    }
+    assert!(
+        build_config
+            .verilator_arguments
+            .contains(&"-GWIDTH=8".into())
+    );
+    assert!(
+        build_config
+            .verilator_arguments
+            .ends_with(&["--x-assign".into(), "unique".into()])
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn rejects_systemc_output() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &["src".as_ref()],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let config = VerilatedModelConfig {
+        extra_verilator_args: vec!["--sc".into()],
+        ..Default::default()
+    };
+    assert!(
+        runtime
+            .effective_build_config(
+                "main",
+                "src/main.sv",
+                &[
+                    ("medium_input", 31, 0, PortDirection::Input),
+                    ("medium_output", 31, 0, PortDirection::Output),
+                ],
+                &config,
+            )
+            .is_err()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn sources_from_file_list() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &[],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            file_lists: vec!["src/counter.f".into()],
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )?;
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig {
+            relative_includes: true,
+            ..Default::default()
+        },
+    )?;
+
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    counter.tick().whatever_context("tick")?;
+    assert_eq!(counter.read("count").whatever_context("read")?, 1u8.into());
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, io};
+
+use example_verilog_project::FinalLog;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions, dynamic::DynamicVerilatedModel,
+};
+use snafu::{ResultExt, Whatever};
+
+fn runtime() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/final_log.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+/// Each test uses its own `id`, which names the log the `final` block appends
+/// to.
+fn create_final_log<'ctx>(
+    runtime: &'ctx VerilatorRuntime,
+    id: u8,
+) -> Result<DynamicVerilatedModel<'ctx>, Whatever> {
+    clear_final_log(id)?;
+    let mut model = runtime.create_dyn_model(
+        "final_log",
+        "src/final_log.sv",
+        &[("id", 7, 0, PortDirection::Input)],
+        VerilatedModelConfig::default(),
+    )?;
+    model.pin("id", id).whatever_context("pin")?;
+    model.eval();
+    Ok(model)
+}
+
+fn clear_final_log(id: u8) -> Result<(), Whatever> {
+    match fs::remove_file(format!("artifacts/final_log_{id}.txt")) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            Err(error).whatever_context("Failed to remove final log")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The number of times the `final` block of the model with `id` has run.
+fn final_runs(id: u8) -> usize {
+    fs::read_to_string(format!("artifacts/final_log_{id}.txt"))
+        .map(|log| log.lines().count())
+        .unwrap_or(0)
+}
+
+#[test]
+#[snafu::report]
+fn runtime_finalize_runs_final_blocks_once() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut model = create_final_log(&runtime, 1)?;
+    assert_eq!(final_runs(1), 0);
+
+    runtime.finalize();
+    assert_eq!(final_runs(1), 1);
+
+    runtime.finalize();
+    model.finalize();
+    drop(model);
+    assert_eq!(final_runs(1), 1);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn dynamic_finalize_runs_final_blocks_once() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut model = create_final_log(&runtime, 2)?;
+
+    model.finalize();
+    assert_eq!(final_runs(2), 1);
+
+    model.finalize();
+    runtime.finalize();
+    drop(model);
+    assert_eq!(final_runs(2), 1);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn generated_finalize_runs_final_blocks_once() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    clear_final_log(3)?;
+    let mut model = runtime.create_model_simple::<FinalLog>()?;
+    model.id = 3;
+    model.eval();
+
+    model.finalize();
+    assert_eq!(final_runs(3), 1);
+
+    model.finalize();
+    runtime.finalize();
+    drop(model);
+    assert_eq!(final_runs(3), 1);
+
+    Ok(())
+}
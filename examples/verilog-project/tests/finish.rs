@@ -0,0 +1,70 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::FinalCheck;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions, simulation::SimulationError,
+};
+use snafu::{ResultExt, Whatever};
+
+/// The error state is shared by every model loaded from the same library, so
+/// each test builds its own in `artifact_directory`.
+fn runtime(artifact_directory: &str) -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        artifact_directory.into(),
+        &["src/final_check.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+#[test]
+#[snafu::report]
+fn finish_succeeds_when_final_checks_pass() -> Result<(), Whatever> {
+    let runtime = runtime("artifacts")?;
+
+    let mut final_check = runtime.create_model_simple::<FinalCheck>()?;
+    final_check.ok = 1;
+    final_check.eval();
+
+    final_check
+        .finish()
+        .whatever_context("Simulation should end cleanly")?;
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn finish_reports_failed_final_checks() -> Result<(), Whatever> {
+    let runtime = runtime("artifacts_finish")?;
+
+    let mut final_check = runtime.create_dyn_model(
+        "final_check",
+        "src/final_check.sv",
+        &[("ok", 0, 0, PortDirection::Input)],
+        VerilatedModelConfig::default(),
+    )?;
+    final_check.pin("ok", 0u8).whatever_context("pin")?;
+    final_check.eval();
+
+    assert!(matches!(
+        final_check.finish(),
+        Err(SimulationError::EndedWithError { top_module }) if top_module == "final_check"
+    ));
+
+    Ok(())
+}
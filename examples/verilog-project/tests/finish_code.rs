@@ -0,0 +1,71 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Finisher;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+fn runtime() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/finisher.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+#[test]
+#[snafu::report]
+fn reports_finish() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut finisher = runtime.create_model_simple::<Finisher>()?;
+
+    finisher.tick();
+    assert_eq!(finisher.finish_code(), None);
+
+    finisher.done = 1;
+    finisher.tick();
+    assert_eq!(finisher.finish_code(), Some(0));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn reports_finish_dynamic() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut finisher = runtime.create_dyn_model(
+        "finisher",
+        "src/finisher.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("done", 0, 0, PortDirection::Input),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    finisher.set_clock_port("clk").whatever_context("clock")?;
+
+    finisher.tick().whatever_context("tick")?;
+    assert_eq!(finisher.finish_code(), None);
+
+    finisher.pin("done", 1u8).whatever_context("pin")?;
+    finisher.tick().whatever_context("tick")?;
+    assert_eq!(finisher.finish_code(), Some(0));
+
+    Ok(())
+}
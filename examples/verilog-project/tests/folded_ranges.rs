@@ -0,0 +1,30 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{FoldedRanges, WideFoldedRanges};
+
+#[test]
+fn folds_constant_arithmetic() {
+    assert_eq!(FoldedRanges::SUM_WIDTH, 5);
+    assert_eq!(FoldedRanges::PRODUCT_WIDTH, 8);
+    assert_eq!(FoldedRanges::SHIFTED_WIDTH, 16);
+    assert_eq!(FoldedRanges::NEGATED_WIDTH, 8);
+    assert_eq!(FoldedRanges::QUOTIENT_WIDTH, 12);
+    assert_eq!(FoldedRanges::PARAMETERIZED_WIDTH, 6);
+}
+
+#[test]
+fn folds_overridden_parameters() {
+    assert_eq!(WideFoldedRanges::PARAMETERIZED_WIDTH, 10);
+}
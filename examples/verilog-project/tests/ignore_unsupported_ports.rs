@@ -0,0 +1,45 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::PartialPorts;
+use marlin::verilator::{
+    AsVerilatedModel, VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn binds_supported_ports() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/partial_ports.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let port_names = PartialPorts::ports()
+        .iter()
+        .map(|(name, ..)| *name)
+        .collect::<Vec<_>>();
+    assert_eq!(port_names, ["a", "c"]);
+
+    // the skipped input `b` is held at zero
+    let mut partial_ports = runtime.create_model_simple::<PartialPorts>()?;
+    partial_ports.a = 3;
+    partial_ports.eval();
+    assert_eq!(partial_ports.c, 3);
+
+    Ok(())
+}
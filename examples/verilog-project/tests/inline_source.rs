@@ -0,0 +1,57 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn model_from_in_memory_source() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &[],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let ports = &[
+        ("a", 7, 0, PortDirection::Input),
+        ("b", 7, 0, PortDirection::Output),
+    ];
+    for shift in 1..=3 {
+        let mut shifter = runtime.create_dyn_model_from_source(
+            "shifter",
+            &format!(
+                "module shifter(input[7:0] a, output[7:0] b);
+                    assign b = a << {shift};
+                endmodule"
+            ),
+            ports,
+            VerilatedModelConfig::default(),
+        )?;
+
+        shifter.pin("a", 1u8).whatever_context("pin")?;
+        shifter.eval();
+        assert_eq!(
+            shifter.read("b").whatever_context("read")?,
+            (1u8 << shift).into()
+        );
+    }
+
+    Ok(())
+}
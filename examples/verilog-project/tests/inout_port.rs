@@ -0,0 +1,52 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::InoutBus;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, AsVerilatedModel, PortDirection, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn drives_and_reads_inout_port() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/inout_bus.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut inout_bus = runtime.create_model_simple::<InoutBus>()?;
+
+    assert!(InoutBus::port_descriptors().iter().any(
+        |port| port.name == "bus" && port.direction == PortDirection::Inout
+    ));
+
+    inout_bus.bus = 5;
+    inout_bus.eval();
+    assert_eq!(inout_bus.bus, 5);
+    assert_eq!(inout_bus.echoed, 6);
+
+    inout_bus.pin("bus", 9u8).whatever_context("pin")?;
+    inout_bus.eval();
+    assert_eq!(inout_bus.read("bus").whatever_context("read")?, 9u8.into());
+    assert_eq!(
+        inout_bus.read("echoed").whatever_context("read")?,
+        10u8.into()
+    );
+
+    Ok(())
+}
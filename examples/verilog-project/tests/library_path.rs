@@ -0,0 +1,65 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn finds_built_library() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let ports = &[
+        ("medium_input", 31, 0, PortDirection::Input),
+        ("medium_output", 31, 0, PortDirection::Output),
+    ];
+    let _main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        ports,
+        VerilatedModelConfig::default(),
+    )?;
+
+    let library_path = runtime
+        .library_path_for(
+            "main",
+            "src/main.sv",
+            ports,
+            &VerilatedModelConfig::default(),
+        )
+        .expect("library was just built");
+    assert!(library_path.is_file());
+    assert_eq!(library_path.file_name(), Some("libmarlin_Vmain.so"));
+
+    let unbuilt_config = VerilatedModelConfig {
+        unroll_count: Some(1947),
+        ..Default::default()
+    };
+    assert!(
+        runtime
+            .library_path_for("main", "src/main.sv", ports, &unbuilt_config)
+            .is_none()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+
+use marlin_verilog_macro_builder::list_modules;
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn lists_ansi_modules_in_order() -> Result<(), Whatever> {
+    let modules = list_modules(Path::new("src/module_list.sv"))
+        .whatever_context("Failed to parse src/module_list.sv")?;
+
+    assert_eq!(modules, ["first_listed", "second_listed"]);
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions, manifest::MANIFEST_FILE_NAME,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn writes_manifest_next_to_library() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let ports = &[
+        ("medium_input", 31, 0, PortDirection::Input),
+        ("medium_output", 31, 0, PortDirection::Output),
+    ];
+    let _main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        ports,
+        VerilatedModelConfig::default(),
+    )?;
+
+    let library_path = runtime
+        .library_path_for(
+            "main",
+            "src/main.sv",
+            ports,
+            &VerilatedModelConfig::default(),
+        )
+        .expect("library was just built");
+    let manifest_path = library_path
+        .parent()
+        .and_then(|obj_dir| obj_dir.parent())
+        .expect("library is in a subdirectory of the artifacts")
+        .join(MANIFEST_FILE_NAME);
+    let manifest = fs::read_to_string(&manifest_path)
+        .whatever_context("Failed to read manifest")?;
+
+    assert!(manifest.contains("\"name\": \"main\""));
+    assert!(manifest.contains("\"source_path\": \"src/main.sv\""));
+    assert!(manifest.contains("\"verilator_version\": \"Verilator "));
+    assert!(
+        manifest.contains(&format!("\"library_path\": \"{library_path}\""))
+    );
+
+    Ok(())
+}
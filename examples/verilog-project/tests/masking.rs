@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::SignedSample;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn generated_models_mask_to_port_width() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/signed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut model = runtime.create_model_simple::<SignedSample>()?;
+    assert_eq!(SignedSample::SAMPLE_WIDTH, 12);
+
+    // the field is wider than the port, so it can hold out-of-range bits
+    model.raw = 0xffff;
+    model.eval();
+    assert_eq!(model.masked_raw(), 0xfff);
+    assert_eq!(model.masked_sample(), 0xfff);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn dynamic_reads_mask_to_port_width() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/signed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut model = runtime.create_dyn_model(
+        "signed_sample",
+        "src/signed.sv",
+        &[
+            ("raw", 11, 0, PortDirection::Input),
+            ("sample", 11, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    model.pin("raw", 0xffffu16).whatever_context("pin")?;
+    model.eval();
+    assert_eq!(
+        model.read("sample").whatever_context("read")?,
+        0xfffu16.into()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::Main;
+use marlin::verilator::{VerilatorRuntime, VerilatorRuntimeOptions};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn lists_provided_source_files() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/dpi.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let Err(error) = runtime.create_model_simple::<Main>() else {
+        panic!("src/main.sv was not provided to the runtime");
+    };
+    let message = error.to_string();
+    assert!(message.contains("src/main.sv"), "{message}");
+    assert!(message.contains("src/dpi.sv"), "{message}");
+
+    Ok(())
+}
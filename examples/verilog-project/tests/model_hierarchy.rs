@@ -0,0 +1,71 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn lists_ports_and_submodules() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/hierarchy_top.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let ports = [
+        ("clk", 0, 0, PortDirection::Input),
+        ("value", 3, 0, PortDirection::Output),
+    ];
+    let _model = runtime.create_dyn_model(
+        "hierarchy_top",
+        "src/hierarchy_top.sv",
+        &ports,
+        VerilatedModelConfig::default(),
+    )?;
+
+    let hierarchy = runtime.model_hierarchy(
+        "hierarchy_top",
+        "src/hierarchy_top.sv",
+        &ports,
+        &VerilatedModelConfig::default(),
+    )?;
+
+    // both headers declare the ports, once by reference, but each port is
+    // listed only once
+    assert_eq!(hierarchy.ports, ["clk", "value"]);
+    assert!(
+        hierarchy
+            .scopes
+            .iter()
+            .any(|scope| scope == "hierarchy_top.leaf"),
+        "missing submodule scope in {:?}",
+        hierarchy.scopes
+    );
+    assert!(
+        hierarchy
+            .signals
+            .iter()
+            .any(|signal| signal == "hierarchy_top.leaf.state"),
+        "missing submodule signal in {:?}",
+        hierarchy.signals
+    );
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::NetTypes;
+use marlin::verilator::{VerilatorRuntime, VerilatorRuntimeOptions};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn binds_resolved_nets() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/net_types.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut net_types = runtime.create_model_simple::<NetTypes>()?;
+    net_types.a = 0b1100;
+    net_types.b = 0b1010;
+    net_types.eval();
+    assert_eq!(net_types.c, 0b1000);
+
+    Ok(())
+}
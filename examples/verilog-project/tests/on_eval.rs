@@ -0,0 +1,76 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, rc::Rc};
+
+use example_verilog_project::Main;
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn calls_back_after_each_eval() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref(), "src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let outputs = Rc::new(RefCell::new(vec![]));
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+    main.on_eval({
+        let outputs = outputs.clone();
+        move |main| outputs.borrow_mut().push(main.medium_output as u64)
+    });
+    for medium_input in 1..=3 {
+        main.medium_input = medium_input;
+        main.eval();
+    }
+    assert_eq!(*outputs.borrow(), [1, 2, 3]);
+
+    let counts = Rc::new(RefCell::new(vec![]));
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    counter.set_reset_port("rst").whatever_context("reset")?;
+    counter.reset().whatever_context("reset")?;
+    counter.on_eval({
+        let counts = counts.clone();
+        move |counter| {
+            counts
+                .borrow_mut()
+                .push(counter.read("count").expect("count is an output"));
+        }
+    });
+    counter.tick().whatever_context("tick")?;
+    // a tick evaluates on both clock edges
+    assert_eq!(*counts.borrow(), [0u8.into(), 1u8.into()]);
+
+    Ok(())
+}
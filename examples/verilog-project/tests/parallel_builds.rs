@@ -0,0 +1,68 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::thread;
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("medium_input", 31, 0, PortDirection::Input),
+    ("medium_output", 31, 0, PortDirection::Output),
+];
+
+/// Builds `main` into its own artifacts subdirectory, distinguished by
+/// `unroll_count`, and checks that it works.
+fn build_and_check(unroll_count: usize) -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        PORTS,
+        VerilatedModelConfig {
+            unroll_count: Some(unroll_count),
+            ..Default::default()
+        },
+    )?;
+    main.pin("medium_input", unroll_count as u32)
+        .whatever_context("pin")?;
+    main.eval();
+    assert_eq!(
+        main.read("medium_output").whatever_context("read")?,
+        (unroll_count as u32).into()
+    );
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn builds_distinct_models_from_threads() -> Result<(), Whatever> {
+    thread::scope(|scope| {
+        let builds = (0..4)
+            .map(|index| scope.spawn(move || build_and_check(100 + index)))
+            .collect::<Vec<_>>();
+        builds
+            .into_iter()
+            .try_for_each(|build| build.join().expect("build panicked"))
+    })
+}
@@ -0,0 +1,61 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn truncates_to_port_width() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    main.pin_truncating("medium_input", 0x1_0000_0005u64)
+        .whatever_context("pin")?;
+    main.eval();
+    assert_eq!(
+        main.read("medium_output").whatever_context("read")?,
+        5u32.into()
+    );
+
+    // narrower values are widened rather than rejected
+    assert!(main.pin("medium_input", 6u8).is_err());
+    main.pin_truncating("medium_input", 6u8)
+        .whatever_context("pin")?;
+    main.eval();
+    assert_eq!(
+        main.read("medium_output").whatever_context("read")?,
+        6u32.into()
+    );
+
+    Ok(())
+}
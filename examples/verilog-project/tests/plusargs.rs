@@ -0,0 +1,112 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions, dynamic::VerilatorValue,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn design_reads_plusargs() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/plusargs.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut plusargs = runtime.create_dyn_model(
+        "plusargs",
+        "src/plusargs.sv",
+        &[
+            ("seed", 31, 0, PortDirection::Output),
+            ("verbose", 0, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    plusargs
+        .set_plusargs(&["+SEED=42", "+VERBOSE"])
+        .whatever_context("set_plusargs")?;
+    plusargs.eval();
+
+    assert_eq!(
+        plusargs.read("seed").whatever_context("seed")?,
+        VerilatorValue::IData(42)
+    );
+    assert_eq!(
+        plusargs.read("verbose").whatever_context("verbose")?,
+        VerilatorValue::CData(1)
+    );
+
+    assert!(plusargs.set_plusargs(&["+SEED=4\02"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn plusargs_reach_shared_context() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref(), "src/plusargs.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            shared_context: true,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )?;
+
+    // the shared context is created by the library of this first model
+    let _main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    let mut plusargs = runtime.create_dyn_model(
+        "plusargs",
+        "src/plusargs.sv",
+        &[
+            ("seed", 31, 0, PortDirection::Output),
+            ("verbose", 0, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    // an argument containing a space is still a single argument, so the
+    // design sees `+VERBOSE` but no `+SEED`
+    plusargs
+        .set_plusargs(&["+VERBOSE +SEED=8"])
+        .whatever_context("set_plusargs")?;
+    plusargs.eval();
+
+    assert_eq!(
+        plusargs.read("seed").whatever_context("seed")?,
+        VerilatorValue::IData(0)
+    );
+    assert_eq!(
+        plusargs.read("verbose").whatever_context("verbose")?,
+        VerilatorValue::CData(1)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{Main, ascending_range_test::Ascending};
+use marlin::verilator::{AsVerilatedModel, PortDescriptor, PortDirection};
+
+#[test]
+fn retains_declared_ranges() {
+    assert_eq!(
+        Main::port_descriptors(),
+        [
+            PortDescriptor {
+                name: "medium_input",
+                msb: 31,
+                lsb: 0,
+                direction: PortDirection::Input,
+                declared_range: "[31:0]",
+            },
+            PortDescriptor {
+                name: "medium_output",
+                msb: 31,
+                lsb: 0,
+                direction: PortDirection::Output,
+                declared_range: "[31:0]",
+            },
+        ]
+    );
+    assert!(
+        Ascending::port_descriptors()
+            .iter()
+            .all(|port| port.declared_range == "[0:7]")
+    );
+}
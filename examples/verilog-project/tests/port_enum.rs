@@ -0,0 +1,36 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{Main, MainPort};
+use marlin::verilator::{AsVerilatedModel, PortDirection};
+
+#[test]
+fn port_enum_matches_ports() {
+    assert_eq!(
+        MainPort::ALL,
+        [MainPort::medium_input, MainPort::medium_output]
+    );
+    assert_eq!(MainPort::medium_input.name(), "medium_input");
+    assert_eq!(
+        Main::port_info(MainPort::medium_output),
+        (31, 0, PortDirection::Output)
+    );
+
+    for (port, (name, msb, lsb, direction)) in
+        MainPort::ALL.into_iter().zip(Main::ports())
+    {
+        assert_eq!(port.name(), *name);
+        assert_eq!(Main::port_info(port), (*msb, *lsb, *direction));
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("medium_input", 31, 0, PortDirection::Input),
+    ("medium_output", 31, 0, PortDirection::Output),
+];
+
+fn runtime() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts_precompile".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+#[test]
+#[snafu::report]
+fn model_reuses_precompiled_library() -> Result<(), Whatever> {
+    let library_path = {
+        // as if from a build script
+        let runtime = runtime()?;
+        runtime.precompile(
+            "main",
+            "src/main.sv",
+            PORTS,
+            &VerilatedModelConfig::default(),
+        )?;
+        runtime
+            .library_path_for(
+                "main",
+                "src/main.sv",
+                PORTS,
+                &VerilatedModelConfig::default(),
+            )
+            .expect("library was just precompiled")
+    };
+    let built_at = fs::metadata(&library_path)
+        .and_then(|metadata| metadata.modified())
+        .whatever_context("Failed to read library metadata")?;
+
+    let runtime = runtime()?;
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        PORTS,
+        VerilatedModelConfig::default(),
+    )?;
+    main.pin("medium_input", 5u32).whatever_context("pin")?;
+    main.eval();
+    assert_eq!(
+        main.read("medium_output").whatever_context("read")?,
+        5u32.into()
+    );
+
+    let loaded_at = fs::metadata(&library_path)
+        .and_then(|metadata| metadata.modified())
+        .whatever_context("Failed to read library metadata")?;
+    assert_eq!(
+        built_at, loaded_at,
+        "The precompiled library was not rebuilt"
+    );
+
+    Ok(())
+}
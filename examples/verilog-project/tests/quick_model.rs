@@ -0,0 +1,52 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, ptr};
+
+use example_verilog_project::Main;
+use marlin::verilator::{AsVerilatedModel, VerilatedModelConfig};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn quick_model_reuses_runtime() -> Result<(), Whatever> {
+    let mut library_modified = None;
+    for _ in 0..2 {
+        let mut main = marlin::quick_model::<Main>(&["src/main.sv"])?;
+        main.medium_input = u32::MAX;
+        main.eval();
+        assert_eq!(main.medium_output, u32::MAX);
+
+        let library_path = marlin::quick_runtime(&["src/main.sv"])?
+            .library_path_for(
+                Main::name(),
+                Main::source_path(),
+                Main::ports(),
+                &VerilatedModelConfig::default(),
+            )
+            .expect("library was just built");
+        let modified = fs::metadata(&library_path)
+            .and_then(|metadata| metadata.modified())
+            .whatever_context("Failed to read library modification time")?;
+        // the second model does not rebuild the library
+        assert_eq!(*library_modified.get_or_insert(modified), modified);
+    }
+
+    assert!(ptr::eq(
+        marlin::quick_runtime(&["src/main.sv"])?,
+        marlin::quick_runtime(&["src/main.sv"])?
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{env, process::Command};
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions, dynamic::DynamicVerilatedModel,
+};
+use snafu::{ResultExt, Whatever};
+
+fn display(
+    model: &mut DynamicVerilatedModel,
+    value: u8,
+) -> Result<(), Whatever> {
+    model.pin("value", value).whatever_context("pin value")?;
+    model.pin("clk", 0u8).whatever_context("pin clk")?;
+    model.eval();
+    model.pin("clk", 1u8).whatever_context("pin clk")?;
+    model.eval();
+    Ok(())
+}
+
+/// Prints through `$display` while toggling quiet mode. The model prints
+/// straight to the process's standard output, so
+/// [`suppresses_and_restores_display_output`] runs this in a child process to
+/// observe it.
+#[test]
+#[ignore = "run by suppresses_and_restores_display_output"]
+#[snafu::report]
+fn toggle_quiet() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/quiet_display.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut model = runtime.create_dyn_model(
+        "quiet_display",
+        "src/quiet_display.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("value", 7, 0, PortDirection::Input),
+        ],
+        VerilatedModelConfig {
+            quiet: true,
+            ..Default::default()
+        },
+    )?;
+
+    display(&mut model, 1)?;
+    model.set_quiet(false);
+    display(&mut model, 2)?;
+    model.set_quiet(true);
+    display(&mut model, 3)?;
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn suppresses_and_restores_display_output() -> Result<(), Whatever> {
+    let output = Command::new(
+        env::current_exe().whatever_context("Failed to locate test binary")?,
+    )
+    .args(["toggle_quiet", "--exact", "--ignored", "--nocapture"])
+    .output()
+    .whatever_context("Failed to run toggle_quiet")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(!stdout.contains("quiet_display value=1"), "{stdout}");
+    assert!(stdout.contains("quiet_display value=2"), "{stdout}");
+    assert!(!stdout.contains("quiet_display value=3"), "{stdout}");
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::RadixRanges;
+use marlin::verilator::{VerilatorRuntime, VerilatorRuntimeOptions};
+use snafu::Whatever;
+
+#[test]
+fn evaluates_each_radix() {
+    assert_eq!(RadixRanges::SIZED_HEX_WIDTH, 16);
+    assert_eq!(RadixRanges::HEX_WIDTH, 32);
+    assert_eq!(RadixRanges::BINARY_WIDTH, 8);
+    assert_eq!(RadixRanges::OCTAL_WIDTH, 16);
+    assert_eq!(RadixRanges::BASED_DECIMAL_WIDTH, 10);
+}
+
+#[test]
+#[snafu::report]
+fn builds_with_each_radix() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/radix_ranges.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut radix_ranges = runtime.create_model_simple::<RadixRanges>()?;
+
+    radix_ranges.binary = 0xa5;
+    radix_ranges.eval();
+    assert_eq!(radix_ranges.out, 0xa5);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn reads_narrow_ports_as_little_endian_bytes() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/signed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut model = runtime.create_dyn_model(
+        "signed_sample",
+        "src/signed.sv",
+        &[
+            ("raw", 11, 0, PortDirection::Input),
+            ("sample", 11, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    model
+        .pin_truncating("raw", 0xfabcu16)
+        .whatever_context("pin_truncating")?;
+    model.eval();
+
+    // 12 bits fit in two bytes, the second of which only holds the top nibble
+    assert_eq!(
+        model.read_bytes("sample").whatever_context("read_bytes")?,
+        [0xbc, 0x0a]
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn reads_wide_ports_as_little_endian_bytes() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/wide.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut wide = runtime.create_dyn_model(
+        "wide",
+        "src/wide.sv",
+        &[
+            ("a", 99, 0, PortDirection::Input),
+            ("b", 99, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    wide.pin("a", vec![0x04030201, 0x08070605, 0x0c0b0a09, 0xf])
+        .whatever_context("pin")?;
+    wide.eval();
+
+    // `b` is `a + 1`, stored as four words but read as 13 bytes
+    assert_eq!(
+        wide.read_bytes("b").whatever_context("read_bytes")?,
+        [
+            0x02, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0f
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use example_verilog_project::{Main, MainOutputs};
+use marlin::verilator::{
+    VerilatorRuntime, VerilatorRuntimeOptions, dynamic::VerilatorValue,
+};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn reads_all_outputs() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+
+    main.medium_input = 5;
+    let outputs = main.read_outputs();
+    assert_eq!(outputs, MainOutputs { medium_output: 5 });
+
+    main.medium_input = 6;
+    assert_ne!(main.read_outputs(), outputs);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn maps_outputs_by_name() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut main = runtime.create_model_simple::<Main>()?;
+
+    main.medium_input = 5;
+    main.eval();
+    assert_eq!(
+        main.outputs(),
+        BTreeMap::from([("medium_output", VerilatorValue::IData(5))])
+    );
+
+    // reading the outputs does not evaluate the model
+    main.medium_input = 6;
+    assert_eq!(main.outputs()["medium_output"], VerilatorValue::IData(5));
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    dynamic::DynamicVerilatedModelError,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn reads_signed_ports() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/signed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut model = runtime.create_dyn_model(
+        "signed_sample",
+        "src/signed.sv",
+        &[
+            ("raw", 11, 0, PortDirection::Input),
+            ("sample", 11, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    model
+        .set_signed_port("sample")
+        .whatever_context("set_signed_port")?;
+
+    model.pin("raw", 0xfffu16).whatever_context("pin")?;
+    model.eval();
+    assert_eq!(model.read_signed("sample").whatever_context("read")?, -1);
+
+    model.pin("raw", 0x7ffu16).whatever_context("pin")?;
+    model.eval();
+    assert_eq!(model.read_signed("sample").whatever_context("read")?, 0x7ff);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn rejects_unsigned_ports() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/signed.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut model = runtime.create_dyn_model(
+        "signed_sample",
+        "src/signed.sv",
+        &[
+            ("raw", 11, 0, PortDirection::Input),
+            ("sample", 11, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    model.pin("raw", 0xfffu16).whatever_context("pin")?;
+    model.eval();
+    assert!(matches!(
+        model.read_signed("sample"),
+        Err(DynamicVerilatedModelError::UnsignedPort { .. })
+    ));
+
+    Ok(())
+}
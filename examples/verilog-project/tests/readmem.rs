@@ -0,0 +1,55 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// the search relies on GNU ld, so it is only supported on Linux
+#![cfg(target_os = "linux")]
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn readmem_resolves_against_search_paths() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/rom.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut rom = runtime.create_dyn_model(
+        "rom",
+        "src/rom.sv",
+        &[
+            ("addr", 1, 0, PortDirection::Input),
+            ("data", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig {
+            readmem_search_paths: vec!["src".into()],
+            ..Default::default()
+        },
+    )?;
+
+    for (addr, expected) in [0xdeu8, 0xad, 0xbe, 0xef].into_iter().enumerate() {
+        rom.pin("addr", addr as u8).whatever_context("pin")?;
+        rom.eval();
+        assert_eq!(rom.read("data").whatever_context("read")?, expected.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::{Counter, CounterOutputs};
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+fn runtime() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+#[test]
+#[snafu::report]
+fn records_output_sequence() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut counter = runtime.create_model_simple::<Counter>()?;
+
+    counter.reset();
+    assert_eq!(
+        counter.record_outputs(3),
+        [1, 2, 3].map(|count| CounterOutputs { count })
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn records_output_sequence_dynamic() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    counter.set_reset_port("rst").whatever_context("reset")?;
+
+    counter.reset().whatever_context("reset")?;
+    let counts = counter
+        .record_outputs(3)
+        .whatever_context("record")?
+        .into_iter()
+        .map(|outputs| outputs["count"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [1u8.into(), 2u8.into(), 3u8.into()]);
+
+    Ok(())
+}
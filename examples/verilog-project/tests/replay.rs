@@ -0,0 +1,174 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    dynamic::VerilatorValue,
+    replay::{ReplayError, replay_vcd},
+};
+use snafu::{ResultExt, Whatever};
+
+const HEADER: &str = r#"$timescale 1ps $end
+$scope module TOP $end
+$var wire 32 ! medium_input [31:0] $end
+$var wire 32 " medium_output [31:0] $end
+$scope module main $end
+$var wire 32 ! medium_input [31:0] $end
+$var wire 32 " medium_output [31:0] $end
+$upscope $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+b0 !
+b0 "
+$end
+#10
+b101 !
+b101 "
+"#;
+
+fn runtime() -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )
+}
+
+#[test]
+#[snafu::report]
+fn replays_recorded_inputs() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    fs::write(
+        "replay_matching.vcd",
+        format!("{HEADER}#20\nb1111 !\nb1111 \"\n"),
+    )
+    .whatever_context("Failed to write VCD")?;
+    let steps = replay_vcd(&mut main, "replay_matching.vcd", true)
+        .whatever_context("Failed to replay VCD")?;
+    assert_eq!(steps, 3);
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn reports_diverging_outputs() -> Result<(), Whatever> {
+    let runtime = runtime()?;
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    fs::write(
+        "replay_diverging.vcd",
+        format!("{HEADER}#20\nb1111 !\nb1110 \"\n"),
+    )
+    .whatever_context("Failed to write VCD")?;
+    match replay_vcd(&mut main, "replay_diverging.vcd", true) {
+        Err(ReplayError::Mismatch {
+            time,
+            port,
+            expected,
+            actual,
+        }) => {
+            assert_eq!(time, 20);
+            assert_eq!(port, "medium_output");
+            assert_eq!(expected, 14u32.into());
+            assert_eq!(actual, 15u32.into());
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+
+    // without checking outputs, only the inputs are replayed
+    assert_eq!(
+        replay_vcd(&mut main, "replay_diverging.vcd", false)
+            .whatever_context("Failed to replay VCD")?,
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn replays_ports_wider_than_64_bits() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/wide.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut wide = runtime.create_dyn_model(
+        "wide",
+        "src/wide.sv",
+        &[
+            ("a", 99, 0, PortDirection::Input),
+            ("b", 99, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    // `b` is `a + 1`, so the 65 set bits of `a` carry into bit 65
+    let a = format!("1{}", "1".repeat(64));
+    let b = format!("10{}", "0".repeat(64));
+    fs::write(
+        "replay_wide.vcd",
+        format!(
+            "$scope module wide $end
+$var wire 100 ! a [99:0] $end
+$var wire 100 \" b [99:0] $end
+$upscope $end
+$enddefinitions $end
+#0
+b{a} !
+b{b} \"
+"
+        ),
+    )
+    .whatever_context("Failed to write VCD")?;
+    assert_eq!(
+        replay_vcd(&mut wide, "replay_wide.vcd", true)
+            .whatever_context("Failed to replay VCD")?,
+        1
+    );
+    assert_eq!(
+        wide.read("b").whatever_context("read")?,
+        VerilatorValue::WData(vec![0, 0, 2, 0])
+    );
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use example_verilog_project::CounterN;
+use marlin::verilator::{VerilatorRuntime, VerilatorRuntimeOptions};
+use snafu::Whatever;
+
+#[test]
+#[snafu::report]
+fn resets_active_low() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter_n.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut counter = runtime.create_model_simple::<CounterN>()?;
+
+    // the reset starts out deasserted
+    assert_eq!(counter.rst_n, 1);
+
+    counter.reset();
+    assert_eq!(counter.count, 0);
+    assert_eq!(counter.rst_n, 1);
+
+    counter.tick();
+    counter.tick();
+    assert_eq!(counter.count, 2);
+
+    counter.reset();
+    assert_eq!(counter.count, 0);
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn settles_after_reset() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/settle.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut settle = runtime.create_dyn_model(
+        "settle",
+        "src/settle.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("data", 7, 0, PortDirection::Input),
+            ("out", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    settle.set_clock_port("clk").whatever_context("clock")?;
+    settle.set_reset_port("rst").whatever_context("reset")?;
+    settle
+        .set_post_reset_inputs([("data", 9u8.into())])
+        .whatever_context("post-reset inputs")?;
+
+    // the deasserted reset has not propagated to the output yet
+    settle.reset().whatever_context("reset")?;
+    assert_eq!(settle.read("out").whatever_context("read")?, 0u8.into());
+
+    settle.reset_and_settle(1).whatever_context("reset")?;
+    assert_eq!(settle.read("out").whatever_context("read")?, 9u8.into());
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions, coverage::CoverageKinds,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn writes_toggle_activity() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig {
+            coverage: CoverageKinds {
+                toggle: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?;
+
+    counter.set_clock_port("clk").whatever_context("clock")?;
+    for _ in 0..4 {
+        counter.tick().whatever_context("tick")?;
+    }
+
+    counter.write_saif("artifacts/counter.saif", 4_000)?;
+    let saif = fs::read_to_string("artifacts/counter.saif")
+        .whatever_context("Failed to read SAIF file")?;
+    assert!(saif.contains("(DURATION 4000)"));
+    assert!(saif.contains("(INSTANCE counter"));
+    assert!(saif.contains("(count\\[0\\] (TC "));
+
+    Ok(())
+}
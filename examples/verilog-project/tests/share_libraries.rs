@@ -0,0 +1,79 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+const PORTS: &[(&str, usize, usize, PortDirection)] = &[
+    ("medium_input", 31, 0, PortDirection::Input),
+    ("medium_output", 31, 0, PortDirection::Output),
+];
+
+fn runtime(artifact_directory: &str) -> Result<VerilatorRuntime, Whatever> {
+    VerilatorRuntime::new(
+        artifact_directory.into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            share_libraries: true,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )
+}
+
+#[test]
+#[snafu::report]
+fn runtimes_share_libraries() -> Result<(), Whatever> {
+    let first = runtime("artifacts_share_first")?;
+    let _first_main = first.create_dyn_model(
+        "main",
+        "src/main.sv",
+        PORTS,
+        VerilatedModelConfig::default(),
+    )?;
+
+    let second = runtime("artifacts_share_second")?;
+    let mut second_main = second.create_dyn_model(
+        "main",
+        "src/main.sv",
+        PORTS,
+        VerilatedModelConfig::default(),
+    )?;
+    second_main
+        .pin("medium_input", 5u32)
+        .whatever_context("pin")?;
+    second_main.eval();
+    assert_eq!(
+        second_main.read("medium_output").whatever_context("read")?,
+        5u32.into()
+    );
+
+    // the second runtime never built the library itself
+    assert!(
+        second
+            .library_path_for(
+                "main",
+                "src/main.sv",
+                PORTS,
+                &VerilatedModelConfig::default(),
+            )
+            .is_none()
+    );
+
+    Ok(())
+}
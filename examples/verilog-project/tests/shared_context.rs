@@ -0,0 +1,100 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn models_share_simulation_time() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions {
+            shared_context: true,
+            ..VerilatorRuntimeOptions::default_logging()
+        },
+    )?;
+    assert_eq!(runtime.shared_time(), None);
+
+    let mut main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    let mut inverter = runtime.create_dyn_model_from_source(
+        "inverter",
+        "module inverter(input a, output b); assign b = !a; endmodule",
+        &[
+            ("a", 0, 0, PortDirection::Input),
+            ("b", 0, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+    assert_eq!(runtime.shared_time(), Some(0));
+
+    runtime.set_shared_time(42)?;
+    main.pin("medium_input", 5u32).whatever_context("pin")?;
+    main.eval();
+    inverter.pin("a", 1u8).whatever_context("pin")?;
+    inverter.eval();
+
+    assert_eq!(runtime.shared_time(), Some(42));
+    assert_eq!(
+        main.read("medium_output").whatever_context("read")?,
+        5u32.into()
+    );
+    assert_eq!(inverter.read("b").whatever_context("read")?, 0u8.into());
+
+    runtime.advance_shared_time(8)?;
+    assert_eq!(runtime.shared_time(), Some(50));
+
+    Ok(())
+}
+
+#[test]
+#[snafu::report]
+fn shared_time_requires_shared_context() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/main.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let _main = runtime.create_dyn_model(
+        "main",
+        "src/main.sv",
+        &[
+            ("medium_input", 31, 0, PortDirection::Input),
+            ("medium_output", 31, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    assert_eq!(runtime.shared_time(), None);
+    assert!(runtime.set_shared_time(42).is_err());
+    assert!(runtime.advance_shared_time(1).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    PortDirection, VerilatedModelConfig, VerilatorRuntime,
+    VerilatorRuntimeOptions, simulation::Simulation,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn simulates_with_a_clock() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/counter.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+
+    let mut counter = runtime.create_dyn_model(
+        "counter",
+        "src/counter.sv",
+        &[
+            ("clk", 0, 0, PortDirection::Input),
+            ("rst", 0, 0, PortDirection::Input),
+            ("count", 7, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    let mut simulation = Simulation::new(&mut counter, "clk", 1_000)?;
+    simulation.poke("rst", 1u8).whatever_context("poke")?;
+    simulation.tick().whatever_context("tick")?;
+    assert_eq!(
+        simulation.peek("count").whatever_context("peek")?,
+        0u8.into()
+    );
+
+    simulation.poke("rst", 0u8).whatever_context("poke")?;
+    simulation.run(3).whatever_context("run")?;
+    assert_eq!(
+        simulation.peek("count").whatever_context("peek")?,
+        3u8.into()
+    );
+    assert_eq!(simulation.time_ps(), 4_000);
+
+    assert!(Simulation::new(&mut counter, "clk", 999).is_err());
+
+    Ok(())
+}
@@ -38,15 +38,19 @@ fn forwards_correctly() -> Result<(), Whatever> {
 
     vcd.dump(0);
 
-    main.medium_input = u32::MAX;
-    println!("{}", main.medium_output);
-    assert_eq!(main.medium_output, 0);
-    main.eval();
-    println!("{}", main.medium_output);
-    assert_eq!(main.medium_output, u32::MAX);
+    // the model is borrowed by the VCD, so we access it through the VCD
+    vcd.medium_input = u32::MAX;
+    println!("{}", vcd.medium_output);
+    assert_eq!(vcd.medium_output, 0);
+    vcd.eval();
+    println!("{}", vcd.medium_output);
+    assert_eq!(vcd.medium_output, u32::MAX);
 
     vcd.dump(1);
     vcd.dump(2);
 
+    vcd.close();
+    main.eval();
+
     Ok(())
 }
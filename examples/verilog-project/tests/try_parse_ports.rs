@@ -0,0 +1,72 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+
+use marlin::verilator::PortDirection;
+use marlin_verilog_macro_builder::{
+    ParseError, PortInfo, try_parse_ports, try_parse_ports_skipping_unsupported,
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+fn reports_unsupported_port_with_span() {
+    let error = try_parse_ports(
+        Path::new("src/partial_ports.sv"),
+        "partial_ports",
+        &[],
+        &[],
+    )
+    .expect_err("the `int` port is not supported");
+
+    let ParseError::Unsupported {
+        construct,
+        span: Some(span),
+    } = &error
+    else {
+        panic!("expected an unsupported construct with a span, got {error:?}");
+    };
+    assert_eq!(construct, "data type `int` on port `b`");
+    // the span covers the `int` in `input int b`
+    assert_eq!(span.line, 3);
+    assert_eq!(span.len, 3);
+    assert!(error.to_string().ends_with("on line 3"), "{error}");
+}
+
+#[test]
+#[snafu::report]
+fn skips_unsupported_ports() -> Result<(), Whatever> {
+    let (ports, skipped) = try_parse_ports_skipping_unsupported(
+        Path::new("src/partial_ports.sv"),
+        "partial_ports",
+        &[],
+        &[],
+    )
+    .whatever_context("Failed to parse src/partial_ports.sv")?;
+
+    assert_eq!(
+        ports,
+        [
+            PortInfo::new("a", 7, 0, PortDirection::Input),
+            PortInfo::new("c", 7, 0, PortDirection::Output),
+        ]
+    );
+    assert!(matches!(
+        skipped.as_slice(),
+        [ParseError::Unsupported { construct, span: Some(_) }]
+            if construct == "data type `int` on port `b`"
+    ));
+
+    Ok(())
+}
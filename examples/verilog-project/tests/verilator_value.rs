@@ -0,0 +1,36 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::dynamic::VerilatorValue;
+
+#[test]
+fn checked_values_reject_overflow() {
+    assert_eq!(VerilatorValue::new(0xf, 4).ok(), Some(0xfu8.into()));
+    assert_eq!(VerilatorValue::new(0x1ff, 9).ok(), Some(0x1ffu16.into()));
+    assert_eq!(
+        VerilatorValue::new(u64::MAX, 64).ok(),
+        Some(u64::MAX.into())
+    );
+    assert!(VerilatorValue::new(0x10, 4).is_err());
+    assert!(VerilatorValue::new(1, 0).is_err());
+}
+
+#[test]
+fn masked_values_truncate() {
+    assert_eq!(VerilatorValue::masked(0x1f, 4), 0xfu8.into());
+    assert_eq!(
+        VerilatorValue::masked(u64::MAX, 33),
+        0x1_ffff_ffffu64.into()
+    );
+}
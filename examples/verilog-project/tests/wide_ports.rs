@@ -0,0 +1,68 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free Software
+// Foundation, version 3 of the License only.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use marlin::verilator::{
+    AsDynamicVerilatedModel, PortDirection, VerilatedModelConfig,
+    VerilatorRuntime, VerilatorRuntimeOptions,
+    dynamic::{DynamicVerilatedModelError, VerilatorValue},
+};
+use snafu::{ResultExt, Whatever};
+
+#[test]
+#[snafu::report]
+fn pins_and_reads_wide_ports() -> Result<(), Whatever> {
+    let runtime = VerilatorRuntime::new(
+        "artifacts".into(),
+        &["src/wide.sv".as_ref()],
+        &[],
+        [],
+        VerilatorRuntimeOptions::default_logging(),
+    )?;
+    let mut wide = runtime.create_dyn_model(
+        "wide",
+        "src/wide.sv",
+        &[
+            ("a", 99, 0, PortDirection::Input),
+            ("b", 99, 0, PortDirection::Output),
+        ],
+        VerilatedModelConfig::default(),
+    )?;
+
+    wide.pin("a", vec![u32::MAX, u32::MAX, 0, 0])
+        .whatever_context("pin")?;
+    wide.eval();
+    let b = wide.read("b").whatever_context("read")?;
+    assert_eq!(b, VerilatorValue::WData(vec![0, 0, 1, 0]));
+    assert_eq!(b.to_string(), "0x1_00000000_00000000");
+
+    // the carry out of the top bit is discarded
+    wide.pin("a", VerilatorValue::masked_words([u32::MAX; 4], 100))
+        .whatever_context("pin")?;
+    wide.eval();
+    assert_eq!(
+        wide.read("b").whatever_context("read")?,
+        VerilatorValue::WData(vec![0; 4])
+    );
+
+    assert!(matches!(
+        wide.pin("a", vec![0, 0, 0]),
+        Err(DynamicVerilatedModelError::InvalidPortWidth { .. })
+    ));
+    assert!(matches!(
+        wide.pin("a", u64::MAX),
+        Err(DynamicVerilatedModelError::InvalidPortWidth { .. })
+    ));
+
+    Ok(())
+}
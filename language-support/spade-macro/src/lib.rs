@@ -8,7 +8,9 @@ use std::{env, fs};
 
 use camino::Utf8PathBuf;
 use marlin_verilator::PortDirection;
-use marlin_verilog_macro_builder::{MacroArgs, build_verilated_struct};
+use marlin_verilog_macro_builder::{
+    MacroArgs, PortInfo, build_verilated_struct,
+};
 use proc_macro::TokenStream;
 use spade_parser::logos::Logos;
 
@@ -26,6 +28,33 @@ fn search_for_swim_toml(mut start: Utf8PathBuf) -> Option<Utf8PathBuf> {
 pub fn spade(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as MacroArgs);
 
+    if args.workspace {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`workspace` is not supported here: sources are always relative to the directory containing swim.toml",
+        )
+        .into_compile_error()
+        .into();
+    }
+
+    if args.ignore_unsupported_ports {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`ignore_unsupported_ports` is not supported here",
+        )
+        .into_compile_error()
+        .into();
+    }
+
+    if !args.defines.is_empty() {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`define` is not supported here",
+        )
+        .into_compile_error()
+        .into();
+    }
+
     let manifest_directory = Utf8PathBuf::from(
         env::var("CARGO_MANIFEST_DIR").expect("Please use CARGO"),
     );
@@ -173,7 +202,12 @@ pub fn spade(args: TokenStream, item: TokenStream) -> TokenStream {
 
         let port_msb = spade_simple_type_width(&port_type.inner) - 1;
 
-        ports.push((port_name.inner.0.clone(), port_msb, 0, port_direction));
+        ports.push(PortInfo::new(
+            port_name.inner.0.clone(),
+            port_msb,
+            0,
+            port_direction,
+        ));
     }
 
     build_verilated_struct(
@@ -183,6 +217,11 @@ pub fn spade(args: TokenStream, item: TokenStream) -> TokenStream {
         ports,
         args.clock_port,
         args.reset_port,
+        args.reset_active_low,
+        args.parameters,
+        args.initial_values,
+        args.combinational,
+        vec![],
         item.into(),
     )
     .into()
@@ -4,12 +4,13 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, error, fmt, path::Path};
 
-use marlin_verilator::PortDirection;
+use marlin_verilator::{PortDirection, port_width};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use sv_parser::{self as sv, Locate, RefNode, unwrap_node};
+use syn::ext::IdentExt;
 
 mod util;
 
@@ -19,6 +20,32 @@ pub struct MacroArgs {
 
     pub clock_port: Option<syn::LitStr>,
     pub reset_port: Option<syn::LitStr>,
+    /// Whether the reset port is asserted low, e.g., `rst_n`.
+    pub reset_active_low: bool,
+
+    /// Whether a relative `source_path` is relative to the workspace root
+    /// instead of the crate root.
+    pub workspace: bool,
+
+    /// Module parameters to override, each given as `param("NAME") = value`.
+    /// The value may refer to const generic parameters on the `struct`.
+    pub parameters: Vec<(syn::LitStr, syn::Expr)>,
+
+    /// Values input ports start out with instead of zero, each given as
+    /// `init("PORT") = value`, e.g., so that an active-low reset idles high.
+    pub initial_values: Vec<(syn::LitStr, syn::Expr)>,
+
+    /// Whether ports that cannot be bound are skipped with a warning instead
+    /// of failing compilation.
+    pub ignore_unsupported_ports: bool,
+
+    /// Whether the module is purely combinational, which generates an
+    /// `evaluate` method that settles the outputs without a clock.
+    pub combinational: bool,
+
+    /// Preprocessor macros to define, given as `define(FOO, BAR = 1)`, both
+    /// when parsing ports and when verilating.
+    pub defines: Vec<(String, Option<String>)>,
 }
 
 impl syn::parse::Parse for MacroArgs {
@@ -28,6 +55,13 @@ impl syn::parse::Parse for MacroArgs {
 
         syn::custom_keyword!(clock);
         syn::custom_keyword!(reset);
+        syn::custom_keyword!(reset_active_low);
+        syn::custom_keyword!(workspace);
+        syn::custom_keyword!(param);
+        syn::custom_keyword!(init);
+        syn::custom_keyword!(ignore_unsupported_ports);
+        syn::custom_keyword!(combinational);
+        syn::custom_keyword!(define);
         input.parse::<src>()?;
         input.parse::<syn::Token![=]>()?;
         let source_path = input.parse::<syn::LitStr>()?;
@@ -40,6 +74,13 @@ impl syn::parse::Parse for MacroArgs {
 
         let mut clock_port = None;
         let mut reset_port = None;
+        let mut active_low = false;
+        let mut workspace_relative = false;
+        let mut parameters = vec![];
+        let mut initial_values = vec![];
+        let mut ignore_unsupported = false;
+        let mut combinational_module = false;
+        let mut defines = vec![];
         while input.peek(syn::Token![,]) {
             input.parse::<syn::Token![,]>()?;
 
@@ -52,6 +93,56 @@ impl syn::parse::Parse for MacroArgs {
                 input.parse::<reset>()?;
                 input.parse::<syn::Token![=]>()?;
                 reset_port = Some(input.parse::<syn::LitStr>()?);
+            } else if lookahead.peek(reset_active_low) {
+                input.parse::<reset_active_low>()?;
+                active_low = true;
+            } else if lookahead.peek(workspace) {
+                input.parse::<workspace>()?;
+                input.parse::<syn::Token![=]>()?;
+                workspace_relative = input.parse::<syn::LitBool>()?.value;
+            } else if lookahead.peek(param) {
+                input.parse::<param>()?;
+                let parameter_name;
+                syn::parenthesized!(parameter_name in input);
+                let parameter_name = parameter_name.parse::<syn::LitStr>()?;
+                input.parse::<syn::Token![=]>()?;
+                parameters.push((parameter_name, input.parse::<syn::Expr>()?));
+            } else if lookahead.peek(init) {
+                input.parse::<init>()?;
+                let port_name;
+                syn::parenthesized!(port_name in input);
+                let port_name = port_name.parse::<syn::LitStr>()?;
+                input.parse::<syn::Token![=]>()?;
+                initial_values.push((port_name, input.parse::<syn::Expr>()?));
+            } else if lookahead.peek(ignore_unsupported_ports) {
+                input.parse::<ignore_unsupported_ports>()?;
+                input.parse::<syn::Token![=]>()?;
+                ignore_unsupported = input.parse::<syn::LitBool>()?.value;
+            } else if lookahead.peek(combinational) {
+                input.parse::<combinational>()?;
+                input.parse::<syn::Token![=]>()?;
+                combinational_module = input.parse::<syn::LitBool>()?.value;
+            } else if lookahead.peek(define) {
+                input.parse::<define>()?;
+                let define_list;
+                syn::parenthesized!(define_list in input);
+                while !define_list.is_empty() {
+                    let define_name =
+                        define_list.call(syn::Ident::parse_any)?;
+                    let define_value = if define_list.peek(syn::Token![=]) {
+                        define_list.parse::<syn::Token![=]>()?;
+                        Some(match define_list.parse::<syn::Lit>()? {
+                            syn::Lit::Str(text) => text.value(),
+                            other => quote! { #other }.to_string(),
+                        })
+                    } else {
+                        None
+                    };
+                    defines.push((define_name.to_string(), define_value));
+                    if !define_list.is_empty() {
+                        define_list.parse::<syn::Token![,]>()?;
+                    }
+                }
             } else {
                 return Err(lookahead.error());
             }
@@ -62,17 +153,155 @@ impl syn::parse::Parse for MacroArgs {
             name,
             clock_port,
             reset_port,
+            reset_active_low: active_low,
+            workspace: workspace_relative,
+            parameters,
+            initial_values,
+            ignore_unsupported_ports: ignore_unsupported,
+            combinational: combinational_module,
+            defines,
         })
     }
 }
 
+/// Fields present on every generated model `struct`. A port cannot share one of
+/// these names because its field would clash with them. Ports may share names
+/// with generated methods like `eval` or `tick`, since Rust keeps fields and
+/// methods in separate namespaces.
+const GENERATED_MEMBER_NAMES: &[&str] = &[
+    "_marker",
+    "_runtime",
+    "_unsend_unsync",
+    "eval_model",
+    "final_model",
+    "finish_code_model",
+    "library",
+    "model",
+    "on_eval",
+    "opened_vcd",
+    "set_quiet_model",
+    "vcd_api",
+];
+
+/// Loads `symbol` from `library` in the generated `init_from`, failing with the
+/// symbol and the module `top_name` it belongs to if the library lacks it.
+fn load_symbol(top_name: &syn::LitStr, symbol: &str) -> TokenStream {
+    let symbol_literal = syn::LitStr::new(symbol, top_name.span());
+    let message = syn::LitStr::new(
+        &format!(
+            "Failed to load symbol `{symbol}` of module `{}` from its library, which may be stale",
+            top_name.value()
+        ),
+        top_name.span(),
+    );
+    quote! {
+        *unsafe { library.get(#symbol_literal.as_bytes()) }
+            .whatever_context(#message)?
+    }
+}
+
+/// Produces the identifier used for the field corresponding to `port_name`.
+/// Ports named after Rust keywords (e.g., `type`) are escaped as raw
+/// identifiers (e.g., `r#type`).
+fn port_field_ident(
+    top_name: &syn::LitStr,
+    port_name: &str,
+) -> Result<syn::Ident, syn::Error> {
+    if GENERATED_MEMBER_NAMES.contains(&port_name) {
+        return Err(syn::Error::new_spanned(
+            top_name,
+            format!(
+                "Port `{port_name}` on module `{}` collides with the generated `{port_name}` member of the model struct. Please rename the port",
+                top_name.value()
+            ),
+        ));
+    }
+
+    if syn::parse_str::<syn::Ident>(port_name).is_ok() {
+        return Ok(format_ident!("{}", port_name));
+    }
+
+    if matches!(port_name, "self" | "Self" | "super" | "crate" | "_")
+        || !port_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(syn::Error::new_spanned(
+            top_name,
+            format!(
+                "Port `{port_name}` on module `{}` cannot be represented as a Rust identifier, even as a raw identifier. Please rename the port",
+                top_name.value()
+            ),
+        ));
+    }
+
+    Ok(syn::Ident::new_raw(port_name, top_name.span()))
+}
+
+/// Checks that no two ports in `ports` generate the same member of the model
+/// `struct`, such as a port named `pin_a` clashing with the `pin_a` setter of
+/// an input `a`, or ports differing only in case sharing a `_WIDTH` constant.
+fn check_port_member_collisions(
+    top_name: &syn::LitStr,
+    ports: &[PortInfo],
+) -> Result<(), syn::Error> {
+    let mut fields = HashMap::new();
+    let mut width_consts = HashMap::new();
+    for port in ports {
+        let mut port_fields = vec![port.name.clone()];
+        if matches!(port.direction, PortDirection::Input | PortDirection::Inout)
+        {
+            port_fields.push(format!("pin_{}", port.name));
+        }
+        if matches!(
+            port.direction,
+            PortDirection::Output | PortDirection::Inout
+        ) {
+            port_fields.push(format!("read_{}", port.name));
+        }
+        for field in port_fields {
+            if let Some(other_port) = fields.insert(field.clone(), &port.name) {
+                return Err(syn::Error::new_spanned(
+                    top_name,
+                    format!(
+                        "Ports `{other_port}` and `{}` on module `{}` both generate a model field named `{field}`. Please rename one of the ports",
+                        port.name,
+                        top_name.value()
+                    ),
+                ));
+            }
+        }
+
+        let width_const = format!("{}_WIDTH", port.name.to_uppercase());
+        if let Some(other_port) =
+            width_consts.insert(width_const.clone(), &port.name)
+        {
+            return Err(syn::Error::new_spanned(
+                top_name,
+                format!(
+                    "Ports `{other_port}` and `{}` on module `{}` differ only in case, so both generate the constant `{width_const}`. Please rename one of the ports",
+                    port.name,
+                    top_name.value()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn build_verilated_struct(
     macro_name: &str,
     top_name: syn::LitStr,
     source_path: syn::LitStr,
-    verilog_ports: Vec<(String, usize, usize, PortDirection)>,
+    verilog_ports: Vec<PortInfo>,
     clock_port: Option<syn::LitStr>,
     reset_port: Option<syn::LitStr>,
+    reset_active_low: bool,
+    parameters: Vec<(syn::LitStr, syn::Expr)>,
+    initial_values: Vec<(syn::LitStr, syn::Expr)>,
+    combinational: bool,
+    defines: Vec<(String, Option<String>)>,
     item: TokenStream,
 ) -> TokenStream {
     let crate_name = format_ident!("{}", macro_name);
@@ -83,6 +312,47 @@ pub fn build_verilated_struct(
         }
     };
 
+    // only const generics are supported, since they are what can be turned
+    // into module parameter overrides
+    let mut generic_params = vec![];
+    let mut generic_args = vec![];
+    for param in &item.generics.params {
+        let syn::GenericParam::Const(const_param) = param else {
+            return syn::Error::new_spanned(
+                param,
+                "Only const generic parameters are supported on Verilated models, e.g., to select module parameters with `param(\"NAME\") = N`",
+            )
+            .into_compile_error();
+        };
+        let mut const_param = const_param.clone();
+        const_param.eq_token = None;
+        const_param.default = None;
+        generic_args.push(const_param.ident.clone());
+        generic_params.push(const_param);
+    }
+    if let Some(where_clause) = &item.generics.where_clause {
+        return syn::Error::new_spanned(
+            where_clause,
+            "Where clauses are not supported on Verilated models",
+        )
+        .into_compile_error();
+    }
+
+    let parameters_impl = parameters.iter().map(|(name, value)| {
+        quote! {
+            (#name, (#value).to_string())
+        }
+    });
+    let defines_impl = defines.iter().map(|(name, value)| {
+        let value = match value {
+            Some(value) => quote! { Some(#value) },
+            None => quote! { None },
+        };
+        quote! {
+            (#name, #value)
+        }
+    });
+
     let mut struct_members = vec![];
 
     let mut preeval_impl = vec![];
@@ -91,37 +361,111 @@ pub fn build_verilated_struct(
     let mut other_impl = vec![];
 
     let mut verilated_model_ports_impl = vec![];
+    let mut port_descriptors_impl = vec![];
     let mut verilated_model_init_impl = vec![];
     let mut verilated_model_init_self = vec![];
 
     let mut dynamic_read_arms = vec![];
     let mut dynamic_pin_arms = vec![];
 
+    let port_enum_name = format_ident!("{}Port", item.ident);
+    let mut port_enum_variants = vec![];
+    let mut port_field_idents = vec![];
+    let mut port_name_arms = vec![];
+    let mut port_info_arms = vec![];
+
+    let mut input_struct_members = vec![];
+    let mut apply_inputs_impl = vec![];
+
+    let mut output_struct_members = vec![];
+    let mut read_outputs_impl = vec![];
+    let mut outputs_impl = vec![];
+
+    let mut has_clock = false;
+    let mut reset_port_ident = None;
+
+    let load_new_model =
+        load_symbol(&top_name, &format!("ffi_new_V{}", top_name.value()));
+    let load_eval_model =
+        load_symbol(&top_name, &format!("ffi_V{}_eval", top_name.value()));
+    let load_final_model =
+        load_symbol(&top_name, &format!("ffi_V{}_final", top_name.value()));
+    let load_finish_code_model = load_symbol(
+        &top_name,
+        &format!("ffi_V{}_finish_code", top_name.value()),
+    );
+    let load_set_quiet_model = load_symbol(&top_name, "ffi_marlin_set_quiet");
+    let load_set_dpi_scope = load_symbol(&top_name, "ffi_dpi_scope");
     verilated_model_init_impl.push(quote! {
-        let new_model: extern "C" fn() -> *mut std::ffi::c_void =
-            *unsafe { library.get(concat!("ffi_new_V", #top_name).as_bytes()) }
-                .expect("failed to get symbol");
-        let model = (new_model)();
+        let new_model: extern "C" fn(*mut std::ffi::c_void) -> *mut std::ffi::c_void =
+            #load_new_model;
 
         let eval_model: extern "C" fn(*mut std::ffi::c_void) =
-            *unsafe { library.get(concat!("ffi_V", #top_name, "_eval").as_bytes()) }
-                .expect("failed to get symbol");
+            #load_eval_model;
+
+        let final_model: extern "C" fn(*mut std::ffi::c_void) =
+            #load_final_model;
 
-        let set_dpi_scope: extern "C" fn() =
-            *unsafe { library.get(b"ffi_dpi_scope") }
-                .expect("failed to get dpi scope symbol");
+        let finish_code_model: extern "C" fn(*mut std::ffi::c_void) -> std::ffi::c_int =
+            #load_finish_code_model;
 
-        // Set the DPI scope for exported functions
-        (set_dpi_scope)();
+        let set_quiet_model: extern "C" fn(bool) = #load_set_quiet_model;
+
+        let set_dpi_scope: extern "C" fn() = #load_set_dpi_scope;
     });
     verilated_model_init_self.push(quote! {
         eval_model,
+        final_model,
+        finish_code_model,
+        set_quiet_model,
         model,
         library,
         _marker: std::marker::PhantomData
     });
 
-    for (port_name, port_msb, port_lsb, port_direction) in verilog_ports {
+    if combinational {
+        if let Some(port) = clock_port.as_ref().or(reset_port.as_ref()) {
+            return syn::Error::new_spanned(
+                port,
+                format!(
+                    "Module `{}` is declared combinational, so it cannot have a clock or reset port",
+                    top_name.value()
+                ),
+            )
+            .into_compile_error();
+        }
+    }
+
+    for (port_name, _) in &initial_values {
+        if !verilog_ports
+            .iter()
+            .any(|port| port.name == port_name.value())
+        {
+            return syn::Error::new_spanned(
+                port_name,
+                format!(
+                    "Module `{}` has no port `{}` to give an initial value",
+                    top_name.value(),
+                    port_name.value()
+                ),
+            )
+            .into_compile_error();
+        }
+    }
+
+    if let Err(error) = check_port_member_collisions(&top_name, &verilog_ports)
+    {
+        return error.into_compile_error();
+    }
+
+    for PortInfo {
+        name: port_name,
+        msb: port_msb,
+        lsb: port_lsb,
+        direction: port_direction,
+        declared_range,
+    } in verilog_ports
+    {
         if port_name.chars().any(|c| c == '\\' || c == ' ') {
             return syn::Error::new_spanned(
                 top_name,
@@ -130,7 +474,7 @@ pub fn build_verilated_struct(
             .into_compile_error();
         }
 
-        let port_width = port_msb + 1 - port_lsb;
+        let port_width = port_width(port_msb, port_lsb);
 
         let port_type_name = if port_width <= 8 {
             quote! { CData }
@@ -149,7 +493,12 @@ pub fn build_verilated_struct(
         };
         let port_type = quote! { #crate_name::__reexports::verilator::types::#port_type_name };
 
-        let port_name_ident = format_ident!("{}", port_name);
+        let port_name_ident = match port_field_ident(&top_name, &port_name) {
+            Ok(ident) => ident,
+            Err(error) => {
+                return error.into_compile_error();
+            }
+        };
         let port_documentation = syn::LitStr::new(
             &format!(
                 "Corresponds to Verilog `{port_direction} {port_name}[{port_msb}:{port_lsb}]`."
@@ -160,95 +509,183 @@ pub fn build_verilated_struct(
             #[doc = #port_documentation]
             pub #port_name_ident: #port_type
         });
+        let initial_value = initial_values
+            .iter()
+            .find(|(name, _)| name.value() == port_name);
+        if let Some((initial_value_port, _)) = initial_value {
+            if port_direction == PortDirection::Output {
+                return syn::Error::new_spanned(
+                    initial_value_port,
+                    format!(
+                        "Port `{port_name}` is an {port_direction} port, but only input and inout ports can be given initial values"
+                    ),
+                )
+                .into_compile_error();
+            }
+        }
+        // an active-low reset starts out deasserted
+        let is_active_low_reset = reset_active_low
+            && reset_port
+                .as_ref()
+                .is_some_and(|reset_port| reset_port.value() == port_name);
+        let default_value = if is_active_low_reset {
+            quote! { 1 }
+        } else {
+            quote! { 0 }
+        };
+        let initial_value = initial_value
+            .map_or(default_value, |(_, value)| quote! { (#value) });
         verilated_model_init_self.push(quote! {
-            #port_name_ident: 0 as _
+            #port_name_ident: #initial_value as _
         });
 
         let port_name_literal = syn::LitStr::new(&port_name, top_name.span());
 
-        match port_direction {
-            PortDirection::Input => {
-                let setter = format_ident!("pin_{}", port_name);
-                struct_members.push(quote! {
-                    #[doc(hidden)]
-                    #setter: extern "C" fn(*mut std::ffi::c_void, #port_type)
-                });
-                preeval_impl.push(quote! {
-                    (self.#setter)(self.model, self.#port_name_ident);
-                });
-
-                if let Some(clock_port) = &clock_port {
-                    if clock_port.value().as_str() == port_name {
-                        other_impl.push(quote! {
-                            pub fn tick(&mut self) {
-                                self.#port_name_ident = 0 as _;
-                                self.eval();
-                                self.#port_name_ident = 1 as _;
-                                self.eval();
-                            }
-                        });
-                    }
-                }
+        let width_const = format_ident!("{}_WIDTH", port_name.to_uppercase());
+        let width_documentation = syn::LitStr::new(
+            &format!("The declared width in bits of the port `{port_name}`."),
+            top_name.span(),
+        );
+        let masked_getter = format_ident!("masked_{}", port_name);
+        let masked_documentation = syn::LitStr::new(
+            &format!(
+                "The value of the `{port_name}` field with any bits at or above the declared width of {port_width} cleared."
+            ),
+            top_name.span(),
+        );
+        let port_mask = u64::MAX >> (64 - port_width);
+        other_impl.push(quote! {
+            #[doc = #width_documentation]
+            pub const #width_const: usize = #port_width;
+
+            #[doc = #masked_documentation]
+            pub fn #masked_getter(&self) -> u64 {
+                self.#port_name_ident as u64 & #port_mask
+            }
+        });
 
-                if let Some(reset_port) = &reset_port {
-                    if reset_port.value().as_str() == port_name {
-                        other_impl.push(quote! {
-                            pub fn reset(&mut self) {
-                                self.#port_name_ident = 1 as _;
-                                            self.tick();
-                                            self.#port_name_ident = 0 as _;
-                                        }
-                        });
+        if matches!(port_direction, PortDirection::Input | PortDirection::Inout)
+        {
+            let setter = format_ident!("pin_{}", port_name);
+            struct_members.push(quote! {
+                #[doc(hidden)]
+                #setter: extern "C" fn(*mut std::ffi::c_void, #port_type)
+            });
+            preeval_impl.push(quote! {
+                (self.#setter)(self.model, self.#port_name_ident);
+            });
+
+            input_struct_members.push(quote! {
+                #[doc = #port_documentation]
+                pub #port_name_ident: #port_type
+            });
+            apply_inputs_impl.push(quote! {
+                self.#port_name_ident = inputs.#port_name_ident;
+            });
+
+            if let Some(clock_port) = &clock_port {
+                if clock_port.value().as_str() == port_name {
+                    if port_width != 1 {
+                        return syn::Error::new_spanned(
+                            clock_port,
+                            format!(
+                                "Clock port `{port_name}` must be 1 bit wide, but it is declared as `[{port_msb}:{port_lsb}]` ({port_width} bits)"
+                            ),
+                        )
+                        .into_compile_error();
                     }
-                }
 
-                verilated_model_init_impl.push(quote! {
-                    let #setter: extern "C" fn(*mut std::ffi::c_void, #port_type) =
-                        *unsafe { library.get(concat!("ffi_V", #top_name, "_pin_", #port_name).as_bytes()) }
-                            .expect("failed to get symbol");
-                });
-                verilated_model_init_self.push(quote! { #setter });
-
-                dynamic_pin_arms.push(quote! {
-                    #port_name_literal => {
-                        if let #crate_name::__reexports::verilator::dynamic::VerilatorValue::#port_type_name(inner) = value {
-                            self.#port_name_ident = inner;
-                        } else {
-                            return Err(
-                                #crate_name::__reexports::verilator::dynamic::DynamicVerilatedModelError::InvalidPortWidth {
-                                    top_module: Self::name().to_string(),
-                                    port: port,
-                                    width: #port_width as _,
-                                    attempted_lower: 0,
-                                    attempted_higher: value.width()
-                                },
-                            );
+                    has_clock = true;
+                    other_impl.push(quote! {
+                        pub fn tick(&mut self) {
+                            self.#port_name_ident = 0 as _;
+                            self.eval();
+                            self.#port_name_ident = 1 as _;
+                            self.eval();
                         }
-                    }
-                });
-            }
-            PortDirection::Output => {
-                let getter = format_ident!("read_{}", port_name);
-                struct_members.push(quote! {
-                    #[doc(hidden)]
-                    #getter: extern "C" fn(*mut std::ffi::c_void) -> #port_type
-                });
-                posteval_impl.push(quote! {
-                    self.#port_name_ident = (self.#getter)(self.model);
-                });
 
-                verilated_model_init_impl.push(quote! {
-                    let #getter: extern "C" fn(*mut std::ffi::c_void) -> #port_type =
-                        *unsafe { library.get(concat!("ffi_V", #top_name, "_read_", #port_name).as_bytes()) }
-                            .expect("failed to get symbol");
-                });
-                verilated_model_init_self.push(quote! { #getter });
+                        #[doc = "Performs `cycles` clock cycles, each like `tick`. Like `tick`, this does not dump to any VCD that may be open."]
+                        pub fn run_cycles(&mut self, cycles: u64) {
+                            for _ in 0..cycles {
+                                self.tick();
+                            }
+                        }
+                    });
+                }
+            }
 
-                dynamic_read_arms.push(quote! {
-                    #port_name_literal => Ok(self.#port_name_ident.into())
-                });
+            if let Some(reset_port) = &reset_port {
+                if reset_port.value().as_str() == port_name {
+                    reset_port_ident = Some(port_name_ident.clone());
+                }
             }
-            _ => todo!("Unhandled port direction"),
+
+            let load_setter = load_symbol(
+                &top_name,
+                &format!("ffi_V{}_pin_{port_name}", top_name.value()),
+            );
+            verilated_model_init_impl.push(quote! {
+                let #setter: extern "C" fn(*mut std::ffi::c_void, #port_type) =
+                    #load_setter;
+            });
+            verilated_model_init_self.push(quote! { #setter });
+
+            dynamic_pin_arms.push(quote! {
+                #port_name_literal => {
+                    if let #crate_name::__reexports::verilator::dynamic::VerilatorValue::#port_type_name(inner) = value {
+                        self.#port_name_ident = inner;
+                    } else {
+                        return Err(
+                            #crate_name::__reexports::verilator::dynamic::DynamicVerilatedModelError::InvalidPortWidth {
+                                top_module: Self::name().to_string(),
+                                port: port,
+                                width: #port_width as _,
+                                attempted_lower: 0,
+                                attempted_higher: value.width()
+                            },
+                        );
+                    }
+                }
+            });
+        }
+
+        if matches!(
+            port_direction,
+            PortDirection::Output | PortDirection::Inout
+        ) {
+            let getter = format_ident!("read_{}", port_name);
+            struct_members.push(quote! {
+                #[doc(hidden)]
+                #getter: extern "C" fn(*mut std::ffi::c_void) -> #port_type
+            });
+            posteval_impl.push(quote! {
+                self.#port_name_ident = (self.#getter)(self.model);
+            });
+
+            output_struct_members.push(quote! {
+                #[doc = #port_documentation]
+                pub #port_name_ident: #port_type
+            });
+            read_outputs_impl.push(quote! {
+                #port_name_ident: self.#port_name_ident
+            });
+            outputs_impl.push(quote! {
+                (#port_name_literal, self.#port_name_ident.into())
+            });
+
+            let load_getter = load_symbol(
+                &top_name,
+                &format!("ffi_V{}_read_{port_name}", top_name.value()),
+            );
+            verilated_model_init_impl.push(quote! {
+                let #getter: extern "C" fn(*mut std::ffi::c_void) -> #port_type =
+                    #load_getter;
+            });
+            verilated_model_init_self.push(quote! { #getter });
+
+            dynamic_read_arms.push(quote! {
+                #port_name_literal => Ok(self.#port_name_ident.into())
+            });
         }
 
         let verilated_model_port_direction = match port_direction {
@@ -258,28 +695,242 @@ pub fn build_verilated_struct(
             PortDirection::Output => {
                 quote! { #crate_name::__reexports::verilator::PortDirection::Output }
             }
-            _ => todo!("Other port directions"),
+            PortDirection::Inout => {
+                quote! { #crate_name::__reexports::verilator::PortDirection::Inout }
+            }
         };
 
         verilated_model_ports_impl.push(quote! {
             (#port_name, #port_msb, #port_lsb, #verilated_model_port_direction)
         });
+        port_descriptors_impl.push(quote! {
+            #crate_name::__reexports::verilator::PortDescriptor {
+                name: #port_name,
+                msb: #port_msb,
+                lsb: #port_lsb,
+                direction: #verilated_model_port_direction,
+                declared_range: #declared_range,
+            }
+        });
+        port_enum_variants.push(quote! {
+            #[doc = #port_documentation]
+            #port_name_ident
+        });
+        port_field_idents.push(port_name_ident.clone());
+        port_name_arms.push(quote! {
+            #port_enum_name::#port_name_ident => #port_name
+        });
+        port_info_arms.push(quote! {
+            #port_enum_name::#port_name_ident => (#port_msb, #port_lsb, #verilated_model_port_direction)
+        });
+    }
+
+    if let Some(reset_port) = &reset_port {
+        if reset_port_ident.is_none() {
+            return syn::Error::new_spanned(
+                reset_port,
+                format!(
+                    "Module `{}` has no input port `{}` to use as the reset",
+                    top_name.value(),
+                    reset_port.value()
+                ),
+            )
+            .into_compile_error();
+        }
+    } else if reset_active_low {
+        return syn::Error::new_spanned(
+            top_name,
+            "`reset_active_low` requires a reset port, e.g., `reset = \"rst_n\"`",
+        )
+        .into_compile_error();
     }
 
     struct_members.push(quote! {
         #[doc(hidden)]
         eval_model: extern "C" fn(*mut std::ffi::c_void)
     });
+    struct_members.push(quote! {
+        #[doc(hidden)]
+        final_model: extern "C" fn(*mut std::ffi::c_void)
+    });
+    struct_members.push(quote! {
+        #[doc(hidden)]
+        finish_code_model: extern "C" fn(*mut std::ffi::c_void) -> std::ffi::c_int
+    });
+    struct_members.push(quote! {
+        #[doc(hidden)]
+        set_quiet_model: extern "C" fn(bool)
+    });
 
     let struct_name = item.ident;
     let vis = item.vis;
     let port_count = verilated_model_ports_impl.len();
+
+    let inputs_struct_name = format_ident!("{}Inputs", struct_name);
+    let inputs_struct_documentation = syn::LitStr::new(
+        &format!(
+            "The input ports of [`{struct_name}`], which can be applied all at once with [`{struct_name}::apply_inputs`]."
+        ),
+        top_name.span(),
+    );
+
+    let port_enum_documentation = syn::LitStr::new(
+        &format!(
+            "The ports of [`{struct_name}`], whose shapes are given by [`{struct_name}::port_info`]."
+        ),
+        top_name.span(),
+    );
+
+    let outputs_struct_name = format_ident!("{}Outputs", struct_name);
+    let outputs_struct_documentation = syn::LitStr::new(
+        &format!(
+            "The output ports of [`{struct_name}`], which can be read all at once with [`{struct_name}::read_outputs`]."
+        ),
+        top_name.span(),
+    );
+
+    // the derive goes through the reexport so users need not depend on serde
+    let serialize_derive = cfg!(feature = "serde").then(|| {
+        let serde_path = syn::LitStr::new(
+            &format!("{crate_name}::__reexports::verilator::__reexports::serde"),
+            top_name.span(),
+        );
+        quote! {
+            #[derive(#crate_name::__reexports::verilator::__reexports::serde::Serialize)]
+            #[serde(crate = #serde_path)]
+        }
+    });
+
+    let record_outputs_impl = has_clock.then(|| {
+        quote! {
+            #[doc = "Ticks the clock `cycles` times like `tick`, returning a snapshot of every output port after each cycle as with [`Self::read_outputs`], e.g., to compare against an expected output sequence."]
+            pub fn record_outputs(&mut self, cycles: usize) -> Vec<#outputs_struct_name> {
+                (0..cycles)
+                    .map(|_| {
+                        self.tick();
+                        self.read_outputs()
+                    })
+                    .collect()
+            }
+        }
+    });
+
+    let evaluate_impl = combinational.then(|| {
+        quote! {
+            #[doc = "Evaluates the model until its outputs stop changing, so that every combinational path has settled without a clock. Fails if the outputs have not settled after 100 evaluations, e.g., because of a combinational loop."]
+            pub fn evaluate(&mut self) -> Result<(), #crate_name::__reexports::verilator::__reexports::snafu::Whatever> {
+                let mut outputs = self.read_outputs();
+                for _ in 0..100 {
+                    let settled_outputs = self.read_outputs();
+                    if settled_outputs == outputs {
+                        return Ok(());
+                    }
+                    outputs = settled_outputs;
+                }
+                #crate_name::__reexports::verilator::__reexports::snafu::whatever!(
+                    "Outputs of combinational module `{}` did not settle after 100 evaluations",
+                    #top_name
+                )
+            }
+        }
+    });
+
+    let reset_impl = reset_port_ident.as_ref().map(|reset_port_ident| {
+        let (asserted, deasserted) = if reset_active_low {
+            (quote! { 0 }, quote! { 1 })
+        } else {
+            (quote! { 1 }, quote! { 0 })
+        };
+        let (reset_documentation, cycle_impl) = if has_clock {
+            (
+                "Asserts the reset for one clock cycle like `tick` and then deasserts it. Like writing to the input fields directly, the deasserted reset takes effect on the next [`Self::eval`].",
+                quote! { self.tick(); },
+            )
+        } else {
+            (
+                "Asserts the reset for one evaluation and then deasserts it. Like writing to the input fields directly, the deasserted reset takes effect on the next [`Self::eval`].",
+                quote! { self.eval(); },
+            )
+        };
+        quote! {
+            #[doc = #reset_documentation]
+            pub fn reset(&mut self) {
+                self.#reset_port_ident = #asserted as _;
+                #cycle_impl
+                self.#reset_port_ident = #deasserted as _;
+            }
+
+            #[doc = "Resets the model like `reset` and then evaluates it `settle_evals` times, so that combinational paths depending on the deasserted reset have settled before the outputs are read."]
+            pub fn reset_and_settle(&mut self, settle_evals: usize) {
+                self.reset();
+                for _ in 0..settle_evals {
+                    self.eval();
+                }
+            }
+        }
+    });
+
+    let load_open_trace = load_symbol(
+        &top_name,
+        &format!("ffi_V{}_open_trace", top_name.value()),
+    );
+    let load_dump = load_symbol(&top_name, "ffi_VerilatedVcdC_dump");
+    let load_open_next = load_symbol(&top_name, "ffi_VerilatedVcdC_open_next");
+    let load_flush = load_symbol(&top_name, "ffi_VerilatedVcdC_flush");
+    let load_close_and_delete =
+        load_symbol(&top_name, "ffi_VerilatedVcdC_close_and_delete");
+
+    let reset_to_impl = reset_port_ident.is_some().then(|| {
+        quote! {
+            #[doc = "Resets the model like `reset` and then sets every input port to the corresponding value in `inputs`, so each test starts from a known input state. Like [`Self::apply_inputs`], the new values take effect on the next [`Self::eval`]."]
+            pub fn reset_to(&mut self, inputs: &#inputs_struct_name) {
+                self.reset();
+                self.apply_inputs(inputs);
+            }
+        }
+    });
+
     quote! {
-        #vis struct #struct_name<'ctx> {
+        #[doc = #inputs_struct_documentation]
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+        #serialize_derive
+        #vis struct #inputs_struct_name {
+            #(#input_struct_members),*
+        }
+
+        #[doc = #outputs_struct_documentation]
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+        #serialize_derive
+        #vis struct #outputs_struct_name {
+            #(#output_struct_members),*
+        }
+
+        #[doc = #port_enum_documentation]
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #vis enum #port_enum_name {
+            #(#port_enum_variants),*
+        }
+
+        impl #port_enum_name {
+            #[doc = "Every port, in declaration order."]
+            pub const ALL: [Self; #port_count] = [#(Self::#port_field_idents),*];
+
+            #[doc = "The name of the port in the Verilog source."]
+            pub fn name(self) -> &'static str {
+                match self {
+                    #(#port_name_arms,)*
+                }
+            }
+        }
+
+        #vis struct #struct_name<'ctx, #(#generic_params),*> {
             #[doc(hidden)]
             vcd_api: Option<#crate_name::__reexports::verilator::vcd::__private::VcdApi>,
             #[doc(hidden)]
-            opened_vcd: bool,
+            opened_vcd: Option<#crate_name::__reexports::verilator::vcd::__private::WeakVcd>,
+            #[doc(hidden)]
+            on_eval: Option<Box<dyn FnMut(&Self) + 'ctx>>,
             #(#struct_members),*,
             #[doc = "# Safety\nThe Rust binding to the model will not outlive the dynamic library context (with lifetime `'ctx`) and is dropped when this struct is."]
             #[doc(hidden)]
@@ -287,43 +938,163 @@ pub fn build_verilated_struct(
             #[doc(hidden)]
             library: &'ctx #crate_name::__reexports::libloading::Library,
             #[doc(hidden)]
+            _runtime: &'ctx #crate_name::__reexports::verilator::VerilatorRuntime,
+            #[doc(hidden)]
             _marker: std::marker::PhantomData<&'ctx ()>,
             #[doc(hidden)]
             _unsend_unsync: std::marker::PhantomData<(std::cell::Cell<()>, std::sync::MutexGuard<'static, ()>)>
         }
 
-        impl<'ctx> #struct_name<'ctx> {
+        impl<'ctx, #(#generic_params),*> #struct_name<'ctx, #(#generic_args),*> {
+            #[doc = "The most significant bit, least significant bit, and direction of `port`, like the matching entry of `ports`, but checked at compile time."]
+            pub fn port_info(port: #port_enum_name) -> (usize, usize, #crate_name::__reexports::verilator::PortDirection) {
+                match port {
+                    #(#port_info_arms,)*
+                }
+            }
+
             #[doc = "Equivalent to the Verilator `eval` method."]
             pub fn eval(&mut self) {
                 #(#preeval_impl)*
                 (self.eval_model)(self.model);
                 #(#posteval_impl)*
+                if let Some(mut on_eval) = self.on_eval.take() {
+                    on_eval(self);
+                    self.on_eval = Some(on_eval);
+                }
+            }
+
+            #[doc = "Calls `callback` with the model at the end of every [`Self::eval`], including those made by methods like `tick`, e.g., to log a signal or check an invariant every cycle. This replaces any previously registered callback."]
+            pub fn on_eval(&mut self, callback: impl FnMut(&Self) + 'ctx) {
+                self.on_eval = Some(Box::new(callback));
+            }
+
+            #[doc = "Equivalent to the Verilator `final` method, which runs the `final` blocks in the Verilog source code, and then flushes the VCD opened with [`Self::open_vcd`] if it is still open. Call this once at the end of simulation: the `final` blocks run at most once, so calling this again, or `finalize` on the runtime afterward, only flushes the VCD."]
+            pub fn finalize(&mut self) {
+                if self._runtime.mark_finalized(self.model) {
+                    (self.final_model)(self.model);
+                }
+                if let Some(vcd) = &self.opened_vcd {
+                    vcd.flush();
+                }
+            }
+
+            #[doc = "Ends the simulation of this model: like [`Self::finalize`], runs the `final` blocks in the Verilog source code and flushes the VCD opened with [`Self::open_vcd`], but then reports whether the simulation ended with an error, e.g., from a failed assertion or `$stop`, and frees the model. Errors raised in the `final` blocks are reported instead of aborting the process. A `$finish` is a normal end to a simulation."]
+            pub fn finish(self) -> Result<(), #crate_name::__reexports::verilator::simulation::SimulationError> {
+                let result = #crate_name::__reexports::verilator::simulation::finish_model(self.library, #top_name, self.model);
+                if let Some(vcd) = &self.opened_vcd {
+                    vcd.flush();
+                }
+                result
+            }
+
+            #[doc = "If the design has ended the simulation during an evaluation, returns the conventional exit status: `Some(0)` after a `$finish`, or `Some(1)` if it also ended with an error (see [`Self::finish`]). Otherwise, returns `None`. Verilator does not record the argument of `$finish`, which only sets how much diagnostic information to print, so it does not affect the status."]
+            pub fn finish_code(&self) -> Option<i32> {
+                #crate_name::__reexports::verilator::simulation::finish_code(self.finish_code_model, self.model)
+            }
+
+            #[doc = "Suppresses (if `quiet`) or restores output printed by the model, such as from `$display`. This affects every model sharing the same library, i.e., created from the same module and configuration."]
+            pub fn set_quiet(&mut self, quiet: bool) {
+                (self.set_quiet_model)(quiet);
             }
 
+            #[doc = "Replaces the command-line arguments the model sees with `args`, e.g., `&[\"+SEED=42\"]`, so that the design can read them with `$value$plusargs` and `$test$plusargs`. Call this before the first evaluation so that `initial` blocks observe the arguments. Each of `args` is passed as its own argument, even if it contains spaces. The arguments belong to the model's Verilator context, so they are shared by every model sharing the same library or, with a shared context, the same runtime. Fails if any of `args` contains a null byte."]
+            pub fn set_plusargs(&mut self, args: &[&str]) -> Result<(), #crate_name::__reexports::verilator::__reexports::snafu::Whatever> {
+                use #crate_name::__reexports::verilator::__reexports::snafu::ResultExt;
+
+                let command_args: #crate_name::__reexports::libloading::Symbol<extern "C" fn(*mut std::ffi::c_void, std::ffi::c_int, *const *const std::ffi::c_char)> = unsafe { self.library.get(concat!("ffi_V", #top_name, "_commandArgs").as_bytes()) }
+                    .whatever_context(concat!("Failed to load argument setter for module ", #top_name))?;
+                let c_args = std::iter::once("marlin")
+                    .chain(args.iter().copied())
+                    .map(|arg| std::ffi::CString::new(arg).whatever_context(format!("Plusarg {arg:?} cannot contain null bytes")))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let argv = c_args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+                (*command_args)(self.model, argv.len() as std::ffi::c_int, argv.as_ptr());
+                Ok(())
+            }
+
+            #[doc = "Writes the coverage counted so far to `path` in the format read by `verilator_coverage`. The model must have been built with some coverage enabled in its configuration. The coverage belongs to the model's Verilator context, so it is shared by every model sharing the same library or, with a shared context, the same runtime."]
+            pub fn write_coverage(
+                &self,
+                path: impl std::convert::AsRef<std::path::Path>,
+            ) -> Result<(), #crate_name::__reexports::verilator::__reexports::snafu::Whatever> {
+                #crate_name::__reexports::verilator::coverage::write_coverage_from_library(
+                    self.library,
+                    #top_name,
+                    self.model,
+                    path.as_ref(),
+                )
+            }
+
+            #[doc = "Writes the toggle activity counted so far to a SAIF file at `path`, recording `duration_ps` as the length of the simulation in picoseconds. The model must have been built with toggle coverage enabled in its configuration. Like the coverage written by [`Self::write_coverage`], the activity is shared by every model sharing the same Verilator context."]
+            pub fn write_saif(
+                &self,
+                path: impl std::convert::AsRef<std::path::Path>,
+                duration_ps: u64,
+            ) -> Result<(), #crate_name::__reexports::verilator::__reexports::snafu::Whatever> {
+                #crate_name::__reexports::verilator::saif::write_saif_from_library(
+                    self.library,
+                    #top_name,
+                    self.model,
+                    path.as_ref(),
+                    duration_ps,
+                )
+            }
+
+            #[doc = "Opens a VCD trace of this model at `path`. The returned [`Vcd`](#crate_name::__reexports::verilator::vcd::Vcd) borrows the model until it is closed, and the model can be accessed through it in the meantime."]
             pub fn open_vcd(
                 &mut self,
                 path: impl std::convert::AsRef<std::path::Path>,
-            ) -> #crate_name::__reexports::verilator::vcd::Vcd<'ctx> {
+            ) -> #crate_name::__reexports::verilator::vcd::Vcd<'_, Self> {
                 let path = path.as_ref();
-                if let Some(vcd_api) = &self.vcd_api {
-                    if self.opened_vcd {
+                if let Some(vcd_api) = self.vcd_api {
+                    if self.opened_vcd.is_some() {
                         panic!("Verilator does not support opening multiple VCD traces (see issue #5813). You can instead split the already-opened VCD.");
                     }
                     let c_path = std::ffi::CString::new(path.as_os_str().as_encoded_bytes()).expect("Failed to convert provided VCD path to C string");
                     let vcd_ptr = (vcd_api.open_trace)(self.model, c_path.as_ptr());
-                    self.opened_vcd = true;
-                    #crate_name::__reexports::verilator::vcd::__private::new_vcd(
+                    let mut vcd = #crate_name::__reexports::verilator::vcd::__private::new_vcd(
+                        self,
                         vcd_ptr,
                         vcd_api.dump,
                         vcd_api.open_next,
                         vcd_api.flush,
                         vcd_api.close_and_delete
-                    )
+                    );
+                    let opened_vcd = #crate_name::__reexports::verilator::vcd::__private::downgrade_vcd(&vcd);
+                    vcd.opened_vcd = Some(opened_vcd);
+                    vcd
                 } else {
-                    #crate_name::__reexports::verilator::vcd::__private::new_vcd_useless()
+                    #crate_name::__reexports::verilator::vcd::__private::new_vcd_useless(self)
                 }
             }
 
+            #[doc = "Sets every input port to the corresponding value in `inputs`. Like writing to the input fields directly, the new values take effect on the next [`Self::eval`]."]
+            pub fn apply_inputs(&mut self, inputs: &#inputs_struct_name) {
+                #(#apply_inputs_impl)*
+            }
+
+            #reset_impl
+
+            #reset_to_impl
+
+            #record_outputs_impl
+
+            #evaluate_impl
+
+            #[doc = "Evaluates the model and returns a snapshot of every output port, e.g., to compare against expected values or store per cycle."]
+            pub fn read_outputs(&mut self) -> #outputs_struct_name {
+                self.eval();
+                #outputs_struct_name {
+                    #(#read_outputs_impl),*
+                }
+            }
+
+            #[doc = "The current value of every output port by name, without evaluating the model, e.g., to diff two simulation runs port by port or log them."]
+            pub fn outputs(&self) -> std::collections::BTreeMap<&'static str, #crate_name::__reexports::verilator::dynamic::VerilatorValue> {
+                std::collections::BTreeMap::from([#(#outputs_impl),*])
+            }
+
             /// Get a DPI export function from the model's dynamic library.
             /// This allows calling Verilog functions that are exported via DPI-C from Rust.
             ///
@@ -339,7 +1110,7 @@ pub fn build_verilated_struct(
             #(#other_impl)*
         }
 
-        impl<'ctx> #crate_name::__reexports::verilator::AsVerilatedModel<'ctx> for #struct_name<'ctx> {
+        impl<'ctx, #(#generic_params),*> #crate_name::__reexports::verilator::AsVerilatedModel<'ctx> for #struct_name<'ctx, #(#generic_args),*> {
             fn name() -> &'static str {
                 #top_name
             }
@@ -353,7 +1124,22 @@ pub fn build_verilated_struct(
                 &PORTS
             }
 
-            fn init_from(library: &'ctx #crate_name::__reexports::libloading::Library, tracing_enabled: bool) -> Self {
+            fn port_descriptors() -> &'static [#crate_name::__reexports::verilator::PortDescriptor] {
+                static PORT_DESCRIPTORS: [#crate_name::__reexports::verilator::PortDescriptor; #port_count] = [#(#port_descriptors_impl),*];
+                &PORT_DESCRIPTORS
+            }
+
+            fn parameters() -> Vec<(&'static str, String)> {
+                vec![#(#parameters_impl),*]
+            }
+
+            fn defines() -> Vec<(&'static str, Option<&'static str>)> {
+                vec![#(#defines_impl),*]
+            }
+
+            fn init_from(runtime: &'ctx #crate_name::__reexports::verilator::VerilatorRuntime, library: &'ctx #crate_name::__reexports::libloading::Library, tracing_enabled: bool) -> Result<Self, #crate_name::__reexports::verilator::__reexports::snafu::Whatever> {
+                use #crate_name::__reexports::verilator::__reexports::snafu::ResultExt;
+
                 #(#verilated_model_init_impl)*
 
                 let vcd_api =
@@ -361,26 +1147,35 @@ pub fn build_verilated_struct(
                         use #crate_name::__reexports::verilator::vcd::__private::VcdApi;
 
                         let open_trace: extern "C" fn(*mut std::ffi::c_void, *const std::ffi::c_char) -> *mut std::ffi::c_void =
-                            *unsafe { library.get(concat!("ffi_V", #top_name, "_open_trace").as_bytes()).expect("failed to get open_trace symbol") };
+                            #load_open_trace;
                         let dump: extern "C" fn(*mut std::ffi::c_void, u64) =
-                            *unsafe { library.get(b"ffi_VerilatedVcdC_dump").expect("failed to get dump symbol") };
+                            #load_dump;
                         let open_next: extern "C" fn(*mut std::ffi::c_void, bool) =
-                            *unsafe { library.get(b"ffi_VerilatedVcdC_open_next").expect("failed to get open_next symbol") };
+                            #load_open_next;
                         let flush: extern "C" fn(*mut std::ffi::c_void) =
-                            *unsafe { library.get(b"ffi_VerilatedVcdC_flush").expect("failed to get flush symbol") };
+                            #load_flush;
                         let close_and_delete: extern "C" fn(*mut std::ffi::c_void) =
-                            *unsafe { library.get(b"ffi_VerilatedVcdC_close_and_delete").expect("failed to get close_and_delete symbol") };
+                            #load_close_and_delete;
                         Some(VcdApi { open_trace, dump, open_next, flush, close_and_delete })
                     } else {
                         None
                     };
 
-                Self {
+                // every symbol is loaded before the model is created so that
+                // a missing one does not leak it
+                let model = (new_model)(runtime.shared_context_pointer());
+
+                // Set the DPI scope for exported functions
+                (set_dpi_scope)();
+
+                Ok(Self {
                     vcd_api,
-                    opened_vcd: false,
+                    opened_vcd: None,
+                    on_eval: None,
+                    _runtime: runtime,
                     #(#verilated_model_init_self),*,
                     _unsend_unsync: std::marker::PhantomData
-                }
+                })
             }
 
             unsafe fn model(&self) -> *mut std::ffi::c_void {
@@ -388,7 +1183,22 @@ pub fn build_verilated_struct(
             }
         }
 
-        impl<'ctx> #crate_name::__reexports::verilator::AsDynamicVerilatedModel<'ctx> for #struct_name<'ctx> {
+        impl<#(#generic_params),*> Drop for #struct_name<'_, #(#generic_args),*> {
+            fn drop(&mut self) {
+                // the trace cannot outlive the model it records
+                if let Some(vcd) = &self.opened_vcd {
+                    vcd.close();
+                }
+                // SAFETY: the model is not used after this
+                unsafe { self._runtime.release_model(self.model) };
+            }
+        }
+
+        impl<'ctx, #(#generic_params),*> #crate_name::__reexports::verilator::AsDynamicVerilatedModel<'ctx> for #struct_name<'ctx, #(#generic_args),*> {
+            fn eval(&mut self) {
+                #struct_name::eval(self);
+            }
+
             fn read(
                 &self,
                 port: impl Into<String>,
@@ -435,60 +1245,355 @@ pub fn build_verilated_struct(
     }
 }
 
-pub fn parse_verilog_ports(
-    top_name: &syn::LitStr,
-    source_path: &syn::LitStr,
+/// A location in a Verilog source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    /// The byte offset of the start of the span in the preprocessed source.
+    pub offset: usize,
+    /// The 1-indexed line of the start of the span.
+    pub line: u32,
+    /// The length of the span in bytes.
+    pub len: usize,
+}
+
+impl From<Locate> for SourceSpan {
+    fn from(locate: Locate) -> Self {
+        Self {
+            offset: locate.offset,
+            line: locate.line,
+            len: locate.len,
+        }
+    }
+}
+
+/// A port on a Verilog module, as found by [`try_parse_ports`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PortInfo {
+    pub name: String,
+    /// The left bound of the declared range, e.g., `0` in `[0:7]`.
+    pub msb: usize,
+    /// The right bound of the declared range, e.g., `7` in `[0:7]`.
+    pub lsb: usize,
+    pub direction: PortDirection,
+    /// The packed dimensions exactly as declared, e.g., `[WIDTH-1:0]`, or the
+    /// empty string for a scalar port.
+    pub declared_range: String,
+}
+
+impl PortInfo {
+    /// A port declared with the numeric range `[msb:lsb]`, or as a scalar if
+    /// both are zero, e.g., for ports of generated Verilog.
+    pub fn new(
+        name: impl Into<String>,
+        msb: usize,
+        lsb: usize,
+        direction: PortDirection,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            msb,
+            lsb,
+            direction,
+            declared_range: if msb == 0 && lsb == 0 {
+                String::new()
+            } else {
+                format!("[{msb}:{lsb}]")
+            },
+        }
+    }
+
+    /// The number of bits in the port.
+    pub fn width(&self) -> usize {
+        port_width(self.msb, self.lsb)
+    }
+
+    /// Whether the port was declared with an ascending range like `[0:7]`
+    /// rather than a descending one like `[7:0]`.
+    pub fn is_ascending(&self) -> bool {
+        self.msb < self.lsb
+    }
+}
+
+/// An error from [`try_parse_ports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The source file could not be read, preprocessed, or parsed.
+    Syntax { message: String },
+    /// The source file has no ANSI-style declaration for the module.
+    ModuleNotFound { module: String },
+    /// A port was declared without `input`, `output`, or `inout`.
+    MissingDirection { port: String },
+    /// A port uses an escaped identifier.
+    EscapedName { port: String },
+    /// The source uses a construct Marlin does not support yet.
+    Unsupported {
+        construct: String,
+        span: Option<SourceSpan>,
+    },
+    /// A port width is a constant with `x` or `z` digits.
+    UnknownWidthDigits { span: Option<SourceSpan> },
+    /// A constant in a port width cannot be evaluated, e.g., because it
+    /// divides by zero.
+    InvalidConstant {
+        reason: String,
+        span: Option<SourceSpan>,
+    },
+    /// A port is an interface, which Verilator cannot verilate on the top
+    /// module.
+    InterfacePort {
+        port: String,
+        span: Option<SourceSpan>,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { message } => write!(
+                f,
+                "{message} (Try checking, for instance, that the file exists.)"
+            ),
+            Self::ModuleNotFound { module } => {
+                write!(f, "Could not find module declaration for `{module}`")
+            }
+            Self::MissingDirection { port } => write!(
+                f,
+                "Port `{port}` has no supported direction (`input`, `output`, or `inout`)"
+            ),
+            Self::EscapedName { port } => write!(
+                f,
+                "Port `{port}` has an escaped name, which is not supported"
+            ),
+            Self::Unsupported { construct, span } => {
+                write!(f, "Unsupported {construct}")?;
+                write_line(f, span)
+            }
+            Self::UnknownWidthDigits { span } => {
+                write!(f, "Port width cannot contain x/z digits")?;
+                write_line(f, span)
+            }
+            Self::InvalidConstant { reason, span } => {
+                write!(f, "Constant in port width {reason}")?;
+                write_line(f, span)
+            }
+            Self::InterfacePort { port, span } => {
+                write!(
+                    f,
+                    "Port `{port}` is an interface, which Verilator does not support on the top module"
+                )?;
+                write_line(f, span)?;
+                write!(
+                    f,
+                    " (Try wrapping the module in one that connects the interface to a port for each of its signals.)"
+                )
+            }
+        }
+    }
+}
+
+fn write_line(
+    f: &mut fmt::Formatter<'_>,
+    span: &Option<SourceSpan>,
+) -> fmt::Result {
+    if let Some(span) = span {
+        write!(f, " on line {}", span.line)?;
+    }
+    Ok(())
+}
+
+impl error::Error for ParseError {}
+
+impl ParseError {
+    pub(crate) fn unsupported<'a>(
+        construct: impl Into<String>,
+        node: impl IntoIterator<Item = RefNode<'a>>,
+    ) -> Self {
+        Self::Unsupported {
+            construct: construct.into(),
+            span: first_span(node),
+        }
+    }
+
+    pub(crate) fn invalid_constant<'a>(
+        reason: impl Into<String>,
+        node: impl IntoIterator<Item = RefNode<'a>>,
+    ) -> Self {
+        Self::InvalidConstant {
+            reason: reason.into(),
+            span: first_span(node),
+        }
+    }
+
+    pub(crate) fn unknown_width_digits<'a>(
+        node: impl IntoIterator<Item = RefNode<'a>>,
+    ) -> Self {
+        Self::UnknownWidthDigits {
+            span: first_span(node),
+        }
+    }
+}
+
+/// The location of the first token in `node`, if any.
+fn first_span<'a>(
+    node: impl IntoIterator<Item = RefNode<'a>>,
+) -> Option<SourceSpan> {
+    node.into_iter().find_map(|node| match node {
+        RefNode::Locate(locate) => Some((*locate).into()),
+        _ => None,
+    })
+}
+
+fn parse_file(
     verilog_source_path: &Path,
-) -> Result<Vec<(String, usize, usize, PortDirection)>, proc_macro2::TokenStream>
-{
-    let defines = HashMap::new();
-    let (ast, _) =
-        match sv::parse_sv(verilog_source_path, &defines, &["."], false, false)
-        {
-            Ok(result) => result,
-            Err(error) => {
-                return Err(syn::Error::new_spanned(
-                source_path,
-                error.to_string()
-                    + " (Try checking, for instance, that the file exists.)",
+    defines: &[(String, Option<String>)],
+) -> Result<sv::SyntaxTree, ParseError> {
+    let defines = defines
+        .iter()
+        .map(|(name, value)| {
+            let text = value
+                .as_ref()
+                .map(|value| sv::DefineText::new(value.clone(), None));
+            (
+                name.clone(),
+                Some(sv::Define::new(name.clone(), vec![], text)),
             )
-            .into_compile_error());
-            }
-        };
+        })
+        .collect::<HashMap<_, _>>();
+    let (ast, _) =
+        sv::parse_sv(verilog_source_path, &defines, &["."], false, false)
+            .map_err(|error| ParseError::Syntax {
+                message: error.to_string(),
+            })?;
+    Ok(ast)
+}
+
+/// The name of the module declared by `module`, if it can be traced back to
+/// source code.
+fn module_name<'a>(
+    ast: &'a sv::SyntaxTree,
+    module: RefNode,
+) -> Option<&'a str> {
+    // taken from https://github.com/dalance/sv-parser/blob/master/README.md
+    fn get_identifier(node: RefNode) -> Option<Locate> {
+        match unwrap_node!(node, SimpleIdentifier, EscapedIdentifier) {
+            Some(RefNode::SimpleIdentifier(x)) => Some(x.nodes.0),
+            Some(RefNode::EscapedIdentifier(x)) => Some(x.nodes.0),
+            _ => None,
+        }
+    }
+
+    let id = unwrap_node!(module, ModuleIdentifier)?;
+    let id = get_identifier(id)?;
+    ast.get_str_trim(&id)
+}
+
+/// Lists the names of every module declared in the file at
+/// `verilog_source_path` that can be bound to a model, in the order they are
+/// declared. Like [`try_parse_ports`], this never panics.
+///
+/// Only modules with ANSI-style port lists are listed, since those are the
+/// only ones whose ports Marlin can parse.
+///
+/// Note that each module bound to a model is verilated into its own library,
+/// so binding several modules from one file verilates the file once per
+/// module.
+pub fn list_modules(
+    verilog_source_path: &Path,
+) -> Result<Vec<String>, ParseError> {
+    let ast = parse_file(verilog_source_path, &[])?;
+    Ok((&ast)
+        .into_iter()
+        .filter(|node| matches!(node, RefNode::ModuleDeclarationAnsi(_)))
+        .filter_map(|module| module_name(&ast, module))
+        .map(str::to_string)
+        .collect())
+}
+
+/// The module parameters overridden by `parameters`, as given to the macro with
+/// `param("NAME") = value`, each with its value if it is an integer literal.
+/// Other values, e.g., const generic parameters, are not known until the model
+/// is created, so ports whose widths depend on them cannot be parsed.
+pub fn parameter_overrides(
+    parameters: &[(syn::LitStr, syn::Expr)],
+) -> Vec<(String, Option<i64>)> {
+    parameters
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(value),
+                    ..
+                }) => value.base10_parse().ok(),
+                _ => None,
+            };
+            (name.value(), value)
+        })
+        .collect()
+}
+
+/// Finds the ports of the module `top_module` defined in the file at
+/// `verilog_source_path` with the preprocessor macros `defines` defined and
+/// the module parameters `parameters` overridden (see [`parameter_overrides`]).
+/// Unlike [`parse_verilog_ports`], this never panics, even on constructs Marlin
+/// does not support, so it is suitable for tooling like editor integrations.
+pub fn try_parse_ports(
+    verilog_source_path: &Path,
+    top_module: &str,
+    defines: &[(String, Option<String>)],
+    parameters: &[(String, Option<i64>)],
+) -> Result<Vec<PortInfo>, ParseError> {
+    parse_ports(verilog_source_path, top_module, defines, parameters)?
+        .into_iter()
+        .collect()
+}
+
+/// Like [`try_parse_ports`], but skips each port that cannot be parsed
+/// instead of failing, returning the ports found along with the reasons the
+/// others were skipped.
+pub fn try_parse_ports_skipping_unsupported(
+    verilog_source_path: &Path,
+    top_module: &str,
+    defines: &[(String, Option<String>)],
+    parameters: &[(String, Option<i64>)],
+) -> Result<(Vec<PortInfo>, Vec<ParseError>), ParseError> {
+    let mut ports = vec![];
+    let mut skipped = vec![];
+    for port in
+        parse_ports(verilog_source_path, top_module, defines, parameters)?
+    {
+        match port {
+            Ok(port) => ports.push(port),
+            Err(error) => skipped.push(error),
+        }
+    }
+    Ok((ports, skipped))
+}
+
+/// Parses each port of the module `top_module` defined in the file at
+/// `verilog_source_path` independently, failing outright only if the module
+/// cannot be found.
+fn parse_ports(
+    verilog_source_path: &Path,
+    top_module: &str,
+    defines: &[(String, Option<String>)],
+    parameters: &[(String, Option<i64>)],
+) -> Result<Vec<Result<PortInfo, ParseError>>, ParseError> {
+    let ast = parse_file(verilog_source_path, defines)?;
 
     let Some(module) = (&ast).into_iter().find_map(|node| match node {
         RefNode::ModuleDeclarationAnsi(module) => {
-            // taken from https://github.com/dalance/sv-parser/blob/master/README.md
-            fn get_identifier(node: RefNode) -> Option<Locate> {
-                match unwrap_node!(node, SimpleIdentifier, EscapedIdentifier) {
-                    Some(RefNode::SimpleIdentifier(x)) => Some(x.nodes.0),
-                    Some(RefNode::EscapedIdentifier(x)) => Some(x.nodes.0),
-                    _ => None,
-                }
-            }
-
-            let id = unwrap_node!(module, ModuleIdentifier).unwrap();
-            let id = get_identifier(id).unwrap();
-            let id = ast.get_str_trim(&id).unwrap();
-            if id == top_name.value().as_str() {
-                Some(module)
-            } else {
-                None
-            }
+            let id = module_name(&ast, RefNode::ModuleDeclarationAnsi(module))?;
+            if id == top_module { Some(module) } else { None }
         }
         _ => None,
     }) else {
-        return Err(syn::Error::new_spanned(
-            top_name,
-            format!(
-                "Could not find module declaration for `{}` in {}",
-                top_name.value(),
-                source_path.value()
-            ),
-        )
-        .into_compile_error());
+        return Err(ParseError::ModuleNotFound {
+            module: top_module.to_string(),
+        });
     };
 
+    let parameters = util::module_parameters(&ast, module, parameters);
+
     let port_declarations_list = module
         .nodes
         .0
@@ -499,186 +1604,297 @@ pub fn parse_verilog_ports(
         .map(|list| list.contents())
         .unwrap_or(vec![]);
 
-    let mut ports = vec![];
-    for (_, port) in port_declarations_list {
-        match port {
-            sv::AnsiPortDeclaration::Net(net) => {
-                let port_name = ast.get_str_trim(&net.nodes.1.nodes.0).expect(
-                    "Port identifier could not be traced back to source code",
-                );
+    Ok(port_declarations_list
+        .into_iter()
+        .map(|(_, port)| parse_port(&ast, port, &parameters))
+        .collect())
+}
 
-                let (port_direction_node, port_type) = net
+fn parse_port(
+    ast: &sv::SyntaxTree,
+    port: &sv::AnsiPortDeclaration,
+    parameters: &util::ParameterValues,
+) -> Result<PortInfo, ParseError> {
+    match port {
+        sv::AnsiPortDeclaration::Net(net) => {
+            let port_name =
+                ast.get_str_trim(&net.nodes.1.nodes.0).ok_or_else(|| {
+                    ParseError::unsupported(
+                        "port identifier that could not be traced back to source code",
+                        &**net,
+                    )
+                })?;
+
+            let (port_direction_node, port_type) = match &net.nodes.0 {
+                Some(
+                    sv::NetPortHeaderOrInterfacePortHeader::NetPortHeader(
+                        net_port_header,
+                    ),
+                ) => net_port_header
                     .nodes
                     .0
                     .as_ref()
-                    .and_then(|maybe_net_header| match maybe_net_header {
-                        sv::NetPortHeaderOrInterfacePortHeader::NetPortHeader(net_port_header) => {
-                            net_port_header.nodes.0.as_ref().map(|d| (d, &net_port_header.nodes.1))
-                        }
-                        _ => todo!("Other port header"),
-                    })
-                    .ok_or_else(|| {
-                        syn::Error::new_spanned(
-                            source_path,
-                            format!(
-                                "Port `{port_name}` has no supported direction (`input` or `output`)"
-                            ),
-                        )
-                        .into_compile_error()
-                    })?;
-
-                let dimensions: &[sv::PackedDimension] = match port_type {
-                    sv::NetPortType::DataType(net_port_type_data_type) => {
-                        match &net_port_type_data_type.nodes.1 {
-                            sv::DataTypeOrImplicit::DataType(data_type) => {
-                                match &**data_type {
-                                    sv::DataType::Vector(data_type_vector) => {
-                                        &data_type_vector.nodes.2
-                                    }
-                                    other => todo!(
-                                        "Unsupported data type {:?}",
-                                        other
-                                    ),
+                    .map(|d| (d, &net_port_header.nodes.1)),
+                Some(
+                    sv::NetPortHeaderOrInterfacePortHeader::InterfacePortHeader(
+                        interface_port_header,
+                    ),
+                ) => {
+                    return Err(ParseError::InterfacePort {
+                        port: port_name.to_string(),
+                        span: first_span(&**interface_port_header),
+                    });
+                }
+                None => None,
+            }
+            .ok_or_else(|| ParseError::MissingDirection {
+                port: port_name.to_string(),
+            })?;
+
+            let dimensions: &[sv::PackedDimension] = match port_type {
+                sv::NetPortType::DataType(net_port_type_data_type) => {
+                    match &net_port_type_data_type.nodes.1 {
+                        sv::DataTypeOrImplicit::DataType(data_type) => {
+                            match &**data_type {
+                                sv::DataType::Vector(data_type_vector) => {
+                                    &data_type_vector.nodes.2
+                                }
+                                other => {
+                                    return Err(ParseError::unsupported(
+                                        format!(
+                                            "data type `{}` on port `{port_name}`",
+                                            ast.get_str_trim(other)
+                                                .unwrap_or_default()
+                                        ),
+                                        other,
+                                    ));
                                 }
                             }
-                            sv::DataTypeOrImplicit::ImplicitDataType(
-                                implicit_data_type,
-                            ) => &implicit_data_type.nodes.1,
                         }
+                        sv::DataTypeOrImplicit::ImplicitDataType(
+                            implicit_data_type,
+                        ) => &implicit_data_type.nodes.1,
                     }
-                    sv::NetPortType::NetTypeIdentifier(_)
-                    | sv::NetPortType::Interconnect(_) => {
-                        todo!("Port type not yet implemented for net ports")
-                    }
-                };
+                }
+                // Verilator resolves interconnects like any other net
+                sv::NetPortType::Interconnect(interconnect) => {
+                    &interconnect.nodes.1.nodes.1
+                }
+                sv::NetPortType::NetTypeIdentifier(net_type) => {
+                    return Err(ParseError::unsupported(
+                        format!(
+                            "user-defined net type `{}` on port `{port_name}`",
+                            ast.get_str_trim(&**net_type).unwrap_or_default()
+                        ),
+                        port_type,
+                    ));
+                }
+            };
+
+            process_port_common(
+                ast,
+                port_name,
+                dimensions,
+                port_direction_node,
+                parameters,
+            )
+        }
 
-                let port_info = match process_port_common(
-                    &ast,
-                    top_name,
-                    port_name,
-                    dimensions,
-                    port_direction_node,
-                ) {
-                    Ok(port_info) => port_info,
-                    Err(error) => {
-                        return Err(error.into_compile_error());
+        sv::AnsiPortDeclaration::Variable(var) => {
+            let port_name =
+                ast.get_str_trim(&var.nodes.1.nodes.0).ok_or_else(|| {
+                    ParseError::unsupported(
+                        "port identifier that could not be traced back to source code",
+                        &**var,
+                    )
+                })?;
+
+            let (port_direction_node, port_type) = var
+                .nodes
+                .0
+                .as_ref()
+                .and_then(|header| {
+                    header.nodes.0.as_ref().map(|d| (d, &header.nodes.1))
+                })
+                .ok_or_else(|| ParseError::MissingDirection {
+                    port: port_name.to_string(),
+                })?;
+
+            let data_type_or_implicit = match &port_type.nodes.0 {
+                sv::VarDataType::DataType(data_type) => Ok(&**data_type),
+                sv::VarDataType::Var(var_data_type_var) => {
+                    match &var_data_type_var.nodes.1 {
+                        sv::DataTypeOrImplicit::DataType(data_type) => {
+                            Ok(&**data_type)
+                        }
+                        sv::DataTypeOrImplicit::ImplicitDataType(
+                            implicit_data_type,
+                        ) => Err(&implicit_data_type.nodes.1),
                     }
-                };
-                ports.push(port_info);
-            }
+                }
+            };
+            let dimensions: &[sv::PackedDimension] = match data_type_or_implicit
+            {
+                Ok(sv::DataType::Vector(data_type_vector)) => {
+                    &data_type_vector.nodes.2
+                }
+                Ok(other) => {
+                    return Err(ParseError::unsupported(
+                        format!(
+                            "data type `{}` on port `{port_name}`",
+                            ast.get_str_trim(other).unwrap_or_default()
+                        ),
+                        other,
+                    ));
+                }
+                Err(implicit_dimensions) => implicit_dimensions,
+            };
+
+            process_port_common(
+                ast,
+                port_name,
+                dimensions,
+                port_direction_node,
+                parameters,
+            )
+        }
+        other => {
+            Err(ParseError::unsupported("explicit port declaration", other))
+        }
+    }
+}
 
-            sv::AnsiPortDeclaration::Variable(var) => {
-                let port_name = ast.get_str_trim(&var.nodes.1.nodes.0).expect(
-                    "Port identifier could not be traced back to source code",
-                );
+/// Finds the ports of the module `top_name` defined in the file at
+/// `verilog_source_path` with the preprocessor macros `defines` defined and
+/// the module parameters `parameters` overridden, reporting errors as compile
+/// errors.
+pub fn parse_verilog_ports(
+    top_name: &syn::LitStr,
+    source_path: &syn::LitStr,
+    verilog_source_path: &Path,
+    defines: &[(String, Option<String>)],
+    parameters: &[(String, Option<i64>)],
+) -> Result<Vec<PortInfo>, proc_macro2::TokenStream> {
+    try_parse_ports(verilog_source_path, &top_name.value(), defines, parameters)
+        .map_err(|error| compile_error(top_name, source_path, error))
+}
 
-                let (port_direction_node, port_type) = var
-                    .nodes
-                    .0
-                    .as_ref()
-                    .and_then(|header| {
-                        header.nodes.0.as_ref().map(|d| (d, &header.nodes.1))
-                    })
-                    .ok_or_else(|| {
-                        syn::Error::new_spanned(
-                            source_path,
-                            format!(
-                                "Port `{port_name}` has no supported direction (`input` or `output`)"
-                            ),
-                        )
-                        .into_compile_error()
-                    })?;
+/// Like [`parse_verilog_ports`], but skips each port that cannot be parsed,
+/// returning along with the other ports a compile warning for each skipped
+/// one that should be emitted with the generated code.
+pub fn parse_verilog_ports_skipping_unsupported(
+    top_name: &syn::LitStr,
+    source_path: &syn::LitStr,
+    verilog_source_path: &Path,
+    defines: &[(String, Option<String>)],
+    parameters: &[(String, Option<i64>)],
+) -> Result<(Vec<PortInfo>, proc_macro2::TokenStream), proc_macro2::TokenStream>
+{
+    let (ports, skipped) = try_parse_ports_skipping_unsupported(
+        verilog_source_path,
+        &top_name.value(),
+        defines,
+        parameters,
+    )
+    .map_err(|error| compile_error(top_name, source_path, error))?;
+
+    // there are no compile warnings on stable, so use a deprecated item to
+    // get the message printed
+    let warnings = skipped.into_iter().map(|error| {
+        let note = format!("Ignoring port: {error}");
+        quote::quote_spanned! { source_path.span() =>
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const ignored_port: () = ();
+                ignored_port
+            };
+        }
+    });
 
-                let dimensions: &[sv::PackedDimension] = match &port_type
-                    .nodes
-                    .0
-                {
-                    sv::VarDataType::DataType(data_type) => {
-                        match &**data_type {
-                            sv::DataType::Vector(data_type_vector) => {
-                                &data_type_vector.nodes.2
-                            }
-                            other => todo!("Unsupported data type {:?}", other),
-                        }
-                    }
-                    sv::VarDataType::Var(var_data_type_var) => {
-                        match &var_data_type_var.nodes.1 {
-                            sv::DataTypeOrImplicit::DataType(data_type) => {
-                                match &**data_type {
-                                    sv::DataType::Vector(data_type_vector) => {
-                                        &data_type_vector.nodes.2
-                                    }
-                                    other => todo!(
-                                        "Unsupported data type (in the VarDataType>DataTypeOrImplicit>DataType branch) {:?}",
-                                        other
-                                    ),
-                                }
-                            }
-                            sv::DataTypeOrImplicit::ImplicitDataType(
-                                implicit_data_type,
-                            ) => &implicit_data_type.nodes.1,
-                        }
-                    }
-                };
+    Ok((ports, quote! { #(#warnings)* }))
+}
 
-                let port_info = match process_port_common(
-                    &ast,
-                    top_name,
-                    port_name,
-                    dimensions,
-                    port_direction_node,
-                ) {
-                    Ok(port_info) => port_info,
-                    Err(error) => {
-                        return Err(error.into_compile_error());
-                    }
-                };
-                ports.push(port_info);
-            }
-            _ => todo!("Other types of ports"),
+fn compile_error(
+    top_name: &syn::LitStr,
+    source_path: &syn::LitStr,
+    error: ParseError,
+) -> proc_macro2::TokenStream {
+    match error {
+        ParseError::ModuleNotFound { .. } => syn::Error::new_spanned(
+            top_name,
+            format!("{error} in {}", source_path.value()),
+        ),
+        ParseError::EscapedName { .. } => {
+            syn::Error::new_spanned(top_name, error)
         }
+        _ => syn::Error::new_spanned(source_path, error),
     }
-
-    Ok(ports)
+    .into_compile_error()
 }
 
 fn process_port_common(
     ast: &sv::SyntaxTree,
-    top_name: &syn::LitStr,
     port_name: &str,
     dimensions: &[sv::PackedDimension],
     port_direction_node: &sv::PortDirection,
-) -> Result<(String, usize, usize, PortDirection), syn::Error> {
+    parameters: &util::ParameterValues,
+) -> Result<PortInfo, ParseError> {
     if port_name.chars().any(|c| c == '\\' || c == ' ') {
-        return Err(syn::Error::new_spanned(
-            top_name,
-            "Escaped module names are not supported",
-        ));
+        return Err(ParseError::EscapedName {
+            port: port_name.to_string(),
+        });
     }
 
-    let (port_msb, port_lsb) = match dimensions.len() {
-        0 => (0, 0),
-        1 => match &dimensions[0] {
-            sv::PackedDimension::Range(packed_dimension_range) => {
-                let range = &packed_dimension_range.nodes.0.nodes.1.nodes;
-                (
-                    util::evaluate_numeric_constant_expression(ast, &range.0),
-                    util::evaluate_numeric_constant_expression(ast, &range.2),
-                )
-            }
-            _ => todo!("Unsupported dimension type"),
-        },
-        _ => todo!("Don't support multidimensional ports yet"),
+    let declared_range = dimensions
+        .iter()
+        .filter_map(|dimension| ast.get_str_trim(dimension))
+        .collect::<String>();
+
+    let (port_msb, port_lsb) = match dimensions {
+        [] => (0, 0),
+        [sv::PackedDimension::Range(packed_dimension_range)] => {
+            let range = &packed_dimension_range.nodes.0.nodes.1.nodes;
+            (
+                util::evaluate_numeric_constant_expression(
+                    ast, &range.0, parameters,
+                )?,
+                util::evaluate_numeric_constant_expression(
+                    ast, &range.2, parameters,
+                )?,
+            )
+        }
+        [other] => {
+            return Err(ParseError::unsupported(
+                format!("dimension on port `{port_name}`"),
+                other,
+            ));
+        }
+        [_, second, ..] => {
+            return Err(ParseError::unsupported(
+                format!("multidimensional port `{port_name}`"),
+                second,
+            ));
+        }
     };
 
-    let port_direction = match port_direction_node {
+    let direction = match port_direction_node {
         sv::PortDirection::Input(_) => PortDirection::Input,
         sv::PortDirection::Output(_) => PortDirection::Output,
         sv::PortDirection::Inout(_) => PortDirection::Inout,
         sv::PortDirection::Ref(_) => {
-            todo!("Reference port direction is not supported")
+            return Err(ParseError::unsupported(
+                format!("`ref` direction on port `{port_name}`"),
+                port_direction_node,
+            ));
         }
     };
 
-    Ok((port_name.to_string(), port_msb, port_lsb, port_direction))
+    Ok(PortInfo {
+        name: port_name.to_string(),
+        msb: port_msb,
+        lsb: port_lsb,
+        direction,
+        declared_range,
+    })
 }
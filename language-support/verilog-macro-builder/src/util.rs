@@ -4,71 +4,311 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use sv_parser as sv;
+use std::collections::HashMap;
 
+use sv_parser::{self as sv, RefNode};
+
+use crate::ParseError;
+
+/// The values of the parameters of a module by name, each `None` if it cannot
+/// be determined from the source, e.g., because it is overridden by a const
+/// generic parameter of the model.
+pub type ParameterValues = HashMap<String, Option<i64>>;
+
+/// Finds the values of the parameters declared in `module`, both in its header
+/// and in its body, with each of `overrides` replacing the default value of the
+/// parameter it names. A default value may refer to the parameters declared
+/// before it.
+pub fn module_parameters(
+    ast: &sv::SyntaxTree,
+    module: &sv::ModuleDeclarationAnsi,
+    overrides: &[(String, Option<i64>)],
+) -> ParameterValues {
+    let mut parameters = ParameterValues::new();
+    for node in module {
+        let RefNode::ParamAssignment(param_assignment) = node else {
+            continue;
+        };
+        let Some(name) = ast.get_str_trim(&param_assignment.nodes.0) else {
+            continue;
+        };
+        if parameters.contains_key(name) {
+            continue;
+        }
+        let value = match overrides
+            .iter()
+            .find(|(overridden_name, _)| overridden_name == name)
+        {
+            Some((_, value)) => *value,
+            None => match &param_assignment.nodes.2 {
+                Some((
+                    _,
+                    sv::ConstantParamExpression::ConstantMintypmaxExpression(
+                        default,
+                    ),
+                )) => match &**default {
+                    sv::ConstantMintypmaxExpression::Unary(default) => {
+                        evaluate_constant_expression(ast, default, &parameters)
+                            .ok()
+                    }
+                    sv::ConstantMintypmaxExpression::Ternary(_) => None,
+                },
+                _ => None,
+            },
+        };
+        parameters.insert(name.to_string(), value);
+    }
+    parameters
+}
+
+/// Parses the digits at `locate` in the given `radix`, ignoring `_`
+/// separators. The base prefix and size of a based number (e.g., the `8'h` in
+/// `8'hF`) are separate nodes, so they are never part of the digits.
+fn parse_digits(
+    ast: &sv::SyntaxTree,
+    locate: &sv::Locate,
+    radix: u32,
+    construct: &str,
+    expression: &sv::ConstantExpression,
+) -> Result<i64, ParseError> {
+    let Some(digits) = ast.get_str_trim(locate) else {
+        return Err(ParseError::unsupported(construct, expression));
+    };
+    let digits = digits.replace('_', "");
+    if digits.contains(['x', 'X', 'z', 'Z', '?']) {
+        return Err(ParseError::unknown_width_digits(expression));
+    }
+    i64::from_str_radix(&digits, radix)
+        .map_err(|_| ParseError::unsupported(construct, expression))
+}
+
+/// Evaluates a constant expression of integer literals and module
+/// `parameters`, e.g., `WIDTH-1` in a packed dimension `[WIDTH-1:0]`, folding
+/// parentheses, unary `+` and `-`, and binary `+`, `-`, `*`, `/`, `<<`, and
+/// `>>`. The result must not be negative.
 pub fn evaluate_numeric_constant_expression(
     ast: &sv::SyntaxTree,
     expression: &sv::ConstantExpression,
-) -> usize {
+    parameters: &ParameterValues,
+) -> Result<usize, ParseError> {
+    let value = evaluate_constant_expression(ast, expression, parameters)?;
+    usize::try_from(value).map_err(|_| {
+        ParseError::invalid_constant(
+            format!("evaluates to {value}, which is negative"),
+            expression,
+        )
+    })
+}
+
+fn evaluate_constant_expression(
+    ast: &sv::SyntaxTree,
+    expression: &sv::ConstantExpression,
+    parameters: &ParameterValues,
+) -> Result<i64, ParseError> {
     match expression {
         sv::ConstantExpression::ConstantPrimary(constant_primary) => {
-            match &**constant_primary {
-                sv::ConstantPrimary::PrimaryLiteral(primary_literal) => {
-                    match &**primary_literal {
-                        sv::PrimaryLiteral::Number(number) => match &**number {
-                            sv::Number::IntegralNumber(integral_number) => {
-                                match &**integral_number {
-                                    sv::IntegralNumber::DecimalNumber(
-                                        decimal_number,
-                                    ) => match &**decimal_number {
-                                        sv::DecimalNumber::UnsignedNumber(
-                                            unsigned_number,
-                                        ) => ast
-                                            .get_str_trim(
-                                                &unsigned_number.nodes.0,
-                                            )
-                                            .unwrap()
-                                            .parse()
-                                            .unwrap(),
-                                        sv::DecimalNumber::BaseUnsigned(
-                                            _decimal_number_base_unsigned,
-                                        ) => todo!(),
-                                        sv::DecimalNumber::BaseXNumber(
-                                            _decimal_number_base_xnumber,
-                                        ) => todo!(),
-                                        sv::DecimalNumber::BaseZNumber(
-                                            _decimal_number_base_znumber,
-                                        ) => todo!(),
-                                    },
-                                    sv::IntegralNumber::OctalNumber(
-                                        _octal_number,
-                                    ) => todo!(),
-                                    sv::IntegralNumber::BinaryNumber(
-                                        _binary_number,
-                                    ) => todo!(),
-                                    sv::IntegralNumber::HexNumber(
-                                        _hex_number,
-                                    ) => todo!(),
+            evaluate_constant_primary(
+                ast,
+                constant_primary,
+                expression,
+                parameters,
+            )
+        }
+        sv::ConstantExpression::Unary(unary) => {
+            let (operator, _, operand) = &unary.nodes;
+            let operand = evaluate_constant_primary(
+                ast, operand, expression, parameters,
+            )?;
+            match ast.get_str_trim(operator) {
+                Some("+") => Ok(operand),
+                Some("-") => operand.checked_neg().ok_or_else(|| {
+                    ParseError::invalid_constant("overflows", expression)
+                }),
+                _ => Err(ParseError::unsupported(
+                    "constant unary operator",
+                    operator,
+                )),
+            }
+        }
+        sv::ConstantExpression::Binary(binary) => {
+            let (left, operator, _, right) = &binary.nodes;
+            let left = evaluate_constant_expression(ast, left, parameters)?;
+            let right = evaluate_constant_expression(ast, right, parameters)?;
+            let shift = |right: i64| {
+                u32::try_from(right).map_err(|_| {
+                    ParseError::invalid_constant(
+                        format!("shifts by {right} bits"),
+                        expression,
+                    )
+                })
+            };
+            let result = match ast.get_str_trim(operator) {
+                Some("+") => left.checked_add(right),
+                Some("-") => left.checked_sub(right),
+                Some("*") => left.checked_mul(right),
+                Some("/") => {
+                    if right == 0 {
+                        return Err(ParseError::invalid_constant(
+                            "divides by zero",
+                            expression,
+                        ));
+                    }
+                    left.checked_div(right)
+                }
+                Some("<<") => {
+                    // `checked_shl` only rejects shifting by too many bits, so
+                    // also check that no bits were shifted out
+                    let shift = shift(right)?;
+                    left.checked_shl(shift)
+                        .filter(|result| result >> shift == left)
+                }
+                Some(">>") => left.checked_shr(shift(right)?),
+                _ => {
+                    return Err(ParseError::unsupported(
+                        "constant binary operator",
+                        operator,
+                    ));
+                }
+            };
+            result.ok_or_else(|| {
+                ParseError::invalid_constant("overflows", expression)
+            })
+        }
+        sv::ConstantExpression::Ternary(_) => Err(ParseError::unsupported(
+            "constant ternary expression",
+            expression,
+        )),
+    }
+}
+
+fn evaluate_constant_primary(
+    ast: &sv::SyntaxTree,
+    constant_primary: &sv::ConstantPrimary,
+    expression: &sv::ConstantExpression,
+    parameters: &ParameterValues,
+) -> Result<i64, ParseError> {
+    let unsupported =
+        |construct: &str| Err(ParseError::unsupported(construct, expression));
+
+    match constant_primary {
+        sv::ConstantPrimary::PrimaryLiteral(primary_literal) => {
+            match &**primary_literal {
+                sv::PrimaryLiteral::Number(number) => match &**number {
+                    sv::Number::IntegralNumber(integral_number) => {
+                        match &**integral_number {
+                            sv::IntegralNumber::DecimalNumber(
+                                decimal_number,
+                            ) => match &**decimal_number {
+                                sv::DecimalNumber::UnsignedNumber(
+                                    unsigned_number,
+                                ) => parse_digits(
+                                    ast,
+                                    &unsigned_number.nodes.0,
+                                    10,
+                                    "decimal constant",
+                                    expression,
+                                ),
+                                sv::DecimalNumber::BaseUnsigned(
+                                    base_unsigned,
+                                ) => parse_digits(
+                                    ast,
+                                    &base_unsigned.nodes.2.nodes.0,
+                                    10,
+                                    "based decimal constant",
+                                    expression,
+                                ),
+                                sv::DecimalNumber::BaseXNumber(_)
+                                | sv::DecimalNumber::BaseZNumber(_) => {
+                                    Err(ParseError::unknown_width_digits(
+                                        expression,
+                                    ))
                                 }
+                            },
+                            sv::IntegralNumber::OctalNumber(octal_number) => {
+                                parse_digits(
+                                    ast,
+                                    &octal_number.nodes.2.nodes.0,
+                                    8,
+                                    "octal constant",
+                                    expression,
+                                )
                             }
-                            sv::Number::RealNumber(_real_number) => {
-                                panic!("Real number")
+                            sv::IntegralNumber::BinaryNumber(binary_number) => {
+                                parse_digits(
+                                    ast,
+                                    &binary_number.nodes.2.nodes.0,
+                                    2,
+                                    "binary constant",
+                                    expression,
+                                )
                             }
-                        },
-                        _ => todo!("Other constant primary literals"),
+                            sv::IntegralNumber::HexNumber(hex_number) => {
+                                parse_digits(
+                                    ast,
+                                    &hex_number.nodes.2.nodes.0,
+                                    16,
+                                    "hexadecimal constant",
+                                    expression,
+                                )
+                            }
+                        }
                     }
-                }
-                _ => panic!("Not a number"),
+                    sv::Number::RealNumber(_) => {
+                        unsupported("real number constant")
+                    }
+                },
+                _ => unsupported("constant primary literal"),
             }
         }
-        sv::ConstantExpression::Unary(_constant_expression_unary) => {
-            todo!("Constant unary expressions")
-        }
-        sv::ConstantExpression::Binary(_constant_expression_binary) => {
-            todo!("Constant binary expressions")
+        sv::ConstantPrimary::MintypmaxExpression(parenthesized) => {
+            match &parenthesized.nodes.0.nodes.1 {
+                sv::ConstantMintypmaxExpression::Unary(inner) => {
+                    evaluate_constant_expression(ast, inner, parameters)
+                }
+                sv::ConstantMintypmaxExpression::Ternary(_) => {
+                    unsupported("constant min:typ:max expression")
+                }
+            }
         }
-        sv::ConstantExpression::Ternary(_constant_expression_ternary) => {
-            todo!("Constant ternary expressions")
+        sv::ConstantPrimary::PsParameter(_)
+        | sv::ConstantPrimary::GenvarIdentifier(_)
+        | sv::ConstantPrimary::FormalPort(_)
+        | sv::ConstantPrimary::Enum(_)
+        | sv::ConstantPrimary::ConstantFunctionCall(_) => {
+            // sv-parser may parse a bare identifier as any of these, even as a
+            // call to a function without arguments
+            let Some(name) = ast
+                .get_str_trim(constant_primary)
+                .filter(|name| is_simple_identifier(name))
+            else {
+                return unsupported("non-numeric constant");
+            };
+            match parameters.get(name) {
+                Some(Some(value)) => Ok(*value),
+                Some(None) => Err(ParseError::invalid_constant(
+                    format!(
+                        "refers to parameter `{name}`, whose value cannot be determined from the source"
+                    ),
+                    expression,
+                )),
+                None => Err(ParseError::invalid_constant(
+                    format!(
+                        "refers to `{name}`, which is not a parameter of the module"
+                    ),
+                    expression,
+                )),
+            }
         }
+        _ => unsupported("non-numeric constant"),
     }
 }
+
+/// Whether `name` is a simple identifier like `WIDTH`, as opposed to, e.g., a
+/// hierarchical or package-scoped name or a function call with arguments.
+fn is_simple_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
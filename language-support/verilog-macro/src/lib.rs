@@ -4,10 +4,14 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::{env, fmt, path::PathBuf};
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
 
 use marlin_verilog_macro_builder::{
-    MacroArgs, build_verilated_struct, parse_verilog_ports,
+    MacroArgs, build_verilated_struct, parameter_overrides,
+    parse_verilog_ports, parse_verilog_ports_skipping_unsupported,
 };
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
@@ -18,20 +22,42 @@ pub fn verilog(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as MacroArgs);
 
     let manifest_directory = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("Please compile using `cargo` or set the `CARGO_MANIFEST_DIR` environment variable"));
-    let source_path = manifest_directory.join(args.source_path.value());
-
-    let ports = match parse_verilog_ports(
-        &args.name,
-        &args.source_path,
-        &source_path,
-    ) {
-        Ok(ports) => ports,
+    let source_path = PathBuf::from(args.source_path.value());
+    let source_path = if source_path.is_absolute() {
+        source_path
+    } else if args.workspace {
+        workspace_directory(&manifest_directory).join(source_path)
+    } else {
+        manifest_directory.join(source_path)
+    };
+
+    let parameters = parameter_overrides(&args.parameters);
+    let parsed_ports = if args.ignore_unsupported_ports {
+        parse_verilog_ports_skipping_unsupported(
+            &args.name,
+            &args.source_path,
+            &source_path,
+            &args.defines,
+            &parameters,
+        )
+    } else {
+        parse_verilog_ports(
+            &args.name,
+            &args.source_path,
+            &source_path,
+            &args.defines,
+            &parameters,
+        )
+        .map(|ports| (ports, quote! {}))
+    };
+    let (ports, warnings) = match parsed_ports {
+        Ok(parsed_ports) => parsed_ports,
         Err(error) => {
             return error.into();
         }
     };
 
-    build_verilated_struct(
+    let verilated_struct = build_verilated_struct(
         "verilog",
         args.name,
         syn::LitStr::new(
@@ -41,11 +67,38 @@ pub fn verilog(args: TokenStream, item: TokenStream) -> TokenStream {
         ports,
         args.clock_port,
         args.reset_port,
+        args.reset_active_low,
+        args.parameters,
+        args.initial_values,
+        args.combinational,
+        args.defines,
         item.into(),
-    )
+    );
+
+    quote! {
+        #warnings
+        #verilated_struct
+    }
     .into()
 }
 
+/// Finds the root of the Cargo workspace containing the crate at
+/// `manifest_directory` the same way Cargo does: the nearest ancestor whose
+/// `Cargo.toml` has a `[workspace]` table. A crate outside any workspace is its
+/// own root.
+fn workspace_directory(manifest_directory: &Path) -> &Path {
+    manifest_directory
+        .ancestors()
+        .find(|directory| {
+            fs::read_to_string(directory.join("Cargo.toml")).is_ok_and(
+                |manifest| {
+                    manifest.lines().any(|line| line.trim() == "[workspace]")
+                },
+            )
+        })
+        .unwrap_or(manifest_directory)
+}
+
 enum DPIPrimitiveType {
     Bool,
     U8,
@@ -132,8 +185,12 @@ fn parse_dpi_primitive_type(
 
 enum DPIType {
     Input(DPIPrimitiveType),
-    /// Veriltor handles output and inout types the same
+    /// Verilator passes both output and inout arguments by pointer. For inout
+    /// arguments, the pointee is the incoming value, which the function may
+    /// read before overwriting it.
     Inout(DPIPrimitiveType),
+    /// Verilator passes string arguments as null-terminated C strings.
+    String,
 }
 
 fn parse_dpi_type(ty: &syn::Type) -> Result<DPIType, syn::Error> {
@@ -148,6 +205,19 @@ fn parse_dpi_type(ty: &syn::Type) -> Result<DPIType, syn::Error> {
             elem,
         }) => {
             if mutability.is_none() {
+                if let syn::Type::Path(type_path) = elem.as_ref() {
+                    if type_path.qself.is_none()
+                        && type_path.path.is_ident("str")
+                    {
+                        if let Some(lifetime) = lifetime {
+                            return Err(syn::Error::new_spanned(
+                                lifetime,
+                                "DPI string type cannot use lifetimes",
+                            ));
+                        }
+                        return Ok(DPIType::String);
+                    }
+                }
                 return Err(syn::Error::new_spanned(
                     and_token,
                     "DPI output or inout type must be represented with a mutable reference",
@@ -170,11 +240,33 @@ fn parse_dpi_type(ty: &syn::Type) -> Result<DPIType, syn::Error> {
         }
         other => Err(syn::Error::new_spanned(
             other,
-            "This type is not supported in DPI. Please use primitive integers or mutable references to them",
+            "This type is not supported in DPI. Please use primitive integers, mutable references to them, or `&str`",
         )),
     }
 }
 
+struct DpiArgs {
+    c_guard: Option<syn::LitStr>,
+}
+
+impl syn::parse::Parse for DpiArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        syn::custom_keyword!(guard);
+
+        if input.is_empty() {
+            return Ok(Self { c_guard: None });
+        }
+
+        input.parse::<guard>()?;
+        input.parse::<syn::Token![=]>()?;
+        let c_guard = input.parse::<syn::LitStr>()?;
+
+        Ok(Self {
+            c_guard: Some(c_guard),
+        })
+    }
+}
+
 /// Marlin allows you to import Rust functions into (System)Verilog over DPI.
 /// The function must have "C" linkage and be imported into SystemVerilog with
 /// "DPI-C" linkage.
@@ -193,8 +285,9 @@ fn parse_dpi_type(ty: &syn::Type) -> Result<DPIType, syn::Error> {
 /// ```
 ///
 /// The Rust function can only take in primitive integer types at or below
-/// 64-bit width and booleans. The order and count of parameters must correspond
-/// exactly with the SystemVerilog import declaration.
+/// 64-bit width, booleans, and string slices. The order and count of
+/// parameters must correspond exactly with the SystemVerilog import
+/// declaration.
 ///
 /// Any `input` parameter on the Verilog side should correspond to a plain
 /// argument on the Rust side. Any `output` or `inout` parmaeter on the Verilog
@@ -208,13 +301,43 @@ fn parse_dpi_type(ty: &syn::Type) -> Result<DPIType, syn::Error> {
 /// | --- | --- |
 /// | `output int foo` | `foo: &mut i32` |
 /// | `input bit bar` | `bar: bool` |
+/// | `inout int unsigned baz` | `baz: &mut u32` |
+/// | `input string qux` | `qux: &str` |
+///
+/// A `string` argument is converted from the C string Verilator passes, with
+/// any invalid UTF-8 replaced by `U+FFFD`.
+///
+/// For an `inout` parameter, the mutable reference initially holds the value
+/// passed in from Verilog, so the function can read it and write back a
+/// response in the same call:
+/// ```ignore
+/// #[verilog::dpi]
+/// pub extern "C" fn bus_transfer(data: &mut u32) {
+///     *data = respond_to(*data);
+/// }
+/// ```
+///
+/// ## Platform-specific functions
+///
+/// If the generated C function should only be compiled under some C
+/// preprocessor condition, pass it as a `guard`:
+/// ```ignore
+/// #[verilog::dpi(guard = "defined(__linux__)")]
+/// pub extern "C" fn page_size(out: &mut u32) {
+///     *out = 4096;
+/// }
+/// ```
+/// Alternatively, since the generated C code only includes the DPI functions
+/// you pass to the runtime, you can select them at the Rust level with
+/// `#[cfg(...)]` on the elements of that array.
 ///
 /// ## More
 ///
 /// Please reference the [Verilator docs on VPI](https://verilator.org/guide/latest/connecting.html#direct-programming-interface-dpi) for further information.
 /// You can also see the [corresponding page in the Marlin handbook](https://www.ethanuppal.com/marlin/verilog/dpi.html).
 #[proc_macro_attribute]
-pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn dpi(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as DpiArgs);
     let item_fn = parse_macro_input!(item as syn::ItemFn);
 
     if !matches!(item_fn.vis, syn::Visibility::Public(_)) {
@@ -331,7 +454,7 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
 
     let attributes = item_fn.attrs;
     let function_name = item_fn.sig.ident;
-    let body = item_fn.block;
+    let body = item_fn.block.stmts;
 
     let struct_name = format_ident!("__DPI_{}", function_name);
 
@@ -348,10 +471,22 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
                 let type_ident = format_ident!("{}", inner.to_string());
                 quote! { *mut #type_ident }
             }
+            DPIType::String => {
+                quote! { *const std::ffi::c_char }
+            }
         };
         parameter_types.push(parameter_type.clone());
+        // the C string is rebound as `#name` in the preamble, so only the
+        // rebinding should carry any `mut`
+        let parameter_name = match dpi_type {
+            DPIType::String => {
+                let ident = &name.ident;
+                quote! { #ident }
+            }
+            _ => quote! { #name },
+        };
         parameters.push(quote! {
-            #(#attributes)* #name: #parameter_type
+            #(#attributes)* #parameter_name: #parameter_type
         });
     }
 
@@ -359,9 +494,21 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
         ports
             .iter()
             .filter_map(|(name, _, dpi_type)| match dpi_type {
+                // the pointer refers to the incoming value for inout arguments,
+                // so the body can read it through the reference
                 DPIType::Inout(_) => Some(quote! {
                     let #name = unsafe { &mut *#name };
                 }),
+                // invalid UTF-8 is replaced rather than panicking, which would
+                // abort the simulation from inside C
+                DPIType::String => {
+                    let ident = &name.ident;
+                    Some(quote! {
+                        let #ident = unsafe { std::ffi::CStr::from_ptr(#ident) }
+                            .to_string_lossy();
+                        let #name: &str = &#ident;
+                    })
+                }
                 _ => None,
             });
 
@@ -370,12 +517,21 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
         function_name.span(),
     );
 
+    let c_guard_impl = args.c_guard.map(|c_guard| {
+        quote! {
+            fn c_guard(&self) -> Option<&'static str> {
+                Some(#c_guard)
+            }
+        }
+    });
+
     let c_signature = ports
         .iter()
         .map(|(name, _, dpi_type)| {
             let c_type = match dpi_type {
                 DPIType::Input(inner) => inner.as_c().to_string(),
                 DPIType::Inout(inner) => format!("{}*", inner.as_c()),
+                DPIType::String => "const char*".to_string(),
             };
             let name_literal =
                 syn::LitStr::new(name.ident.to_string().as_str(), name.span());
@@ -400,7 +556,7 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
             #(#attributes)*
             pub extern "C" fn call(#(#parameters),*) #expanded_return_type {
                 #(#preamble)*
-                #body
+                #(#body)*
             }
         }
 
@@ -420,6 +576,8 @@ pub fn dpi(_args: TokenStream, item: TokenStream) -> TokenStream {
             fn pointer(&self) -> *const std::ffi::c_void {
                 #struct_name::call as extern "C" fn(#(#parameter_types),*) #expanded_return_type as *const std::ffi::c_void
             }
+
+            #c_guard_impl
         }
 
         #[allow(non_upper_case_globals)]
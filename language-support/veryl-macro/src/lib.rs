@@ -8,7 +8,9 @@ use std::{env, fs, iter, str};
 
 use camino::Utf8PathBuf;
 use marlin_verilator::PortDirection;
-use marlin_verilog_macro_builder::{MacroArgs, build_verilated_struct};
+use marlin_verilog_macro_builder::{
+    MacroArgs, PortInfo, build_verilated_struct,
+};
 use proc_macro::TokenStream;
 use veryl_parser::{
     Parser,
@@ -33,7 +35,7 @@ struct ModuleFinder<'args, 'source> {
     args: &'args MacroArgs,
     source_code: &'source str,
     look_for: String,
-    found: Option<Vec<(String, usize, usize, PortDirection)>>,
+    found: Option<Vec<PortInfo>>,
     error: Option<syn::Error>,
 }
 
@@ -165,7 +167,7 @@ impl VerylWalker for ModuleFinder<'_, '_> {
                                 }
                             };
 
-                            ports.push((
+                            ports.push(PortInfo::new(
                                 port_name.to_string(),
                                 port_width,
                                 0,
@@ -194,6 +196,33 @@ impl VerylWalker for ModuleFinder<'_, '_> {
 pub fn veryl(args: TokenStream, item: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as MacroArgs);
 
+    if args.workspace {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`workspace` is not supported here: sources are always relative to the directory containing Veryl.toml",
+        )
+        .into_compile_error()
+        .into();
+    }
+
+    if args.ignore_unsupported_ports {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`ignore_unsupported_ports` is not supported here",
+        )
+        .into_compile_error()
+        .into();
+    }
+
+    if !args.defines.is_empty() {
+        return syn::Error::new_spanned(
+            args.source_path,
+            "`define` is not supported here",
+        )
+        .into_compile_error()
+        .into();
+    }
+
     let manifest_directory = Utf8PathBuf::from(
         env::var("CARGO_MANIFEST_DIR").expect("Please use CARGO"),
     );
@@ -298,6 +327,11 @@ pub fn veryl(args: TokenStream, item: TokenStream) -> TokenStream {
         ports,
         args.clock_port,
         args.reset_port,
+        args.reset_active_low,
+        args.parameters,
+        args.initial_values,
+        args.combinational,
+        vec![],
         item.into(),
     )
     .into()
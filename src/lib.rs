@@ -10,6 +10,9 @@
 #[doc(inline)]
 pub use marlin_verilator as verilator;
 
+#[doc(inline)]
+pub use marlin_verilator::{quick_model, quick_runtime};
+
 #[doc(inline)]
 #[cfg_attr(docsrs, doc(cfg(feature = "verilog")))]
 #[cfg(feature = "verilog")]
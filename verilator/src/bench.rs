@@ -0,0 +1,98 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Standard measurements of how fast models evaluate, e.g., to catch
+//! performance regressions in a design or in the FFI path.
+//!
+//! The durations returned can be reported directly or from a
+//! [Criterion](https://docs.rs/criterion) benchmark with `iter_custom`:
+//!
+//! ```ignore
+//! c.bench_function("main eval", |b| {
+//!     b.iter_custom(|iterations| bench_eval(&mut main, iterations))
+//! });
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    AsDynamicVerilatedModel,
+    dynamic::{DynamicVerilatedModel, DynamicVerilatedModelError},
+};
+
+/// Evaluates `model` `iterations` times, after an untimed warmup of a tenth as
+/// many evaluations, returning the time the timed evaluations took.
+pub fn bench_eval<'ctx>(
+    model: &mut impl AsDynamicVerilatedModel<'ctx>,
+    iterations: u64,
+) -> Duration {
+    for _ in 0..iterations / 10 {
+        model.eval();
+    }
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        model.eval();
+    }
+    start.elapsed()
+}
+
+/// Benchmarks a dynamic model under random inputs. Each iteration pins random
+/// values on the inputs with [`DynamicVerilatedModel::randomize_inputs`] and
+/// then advances the model with [`DynamicVerilatedModel::tick`] if it has a
+/// clock port or [`DynamicVerilatedModel::eval`] otherwise.
+pub struct BenchHarness<F> {
+    next_u64: F,
+    warmup_iterations: u64,
+}
+
+impl<F: FnMut() -> u64> BenchHarness<F> {
+    /// Creates a harness drawing random input values from `next_u64`, which
+    /// should be seeded for repeatable measurements.
+    pub fn new(next_u64: F) -> Self {
+        Self {
+            next_u64,
+            warmup_iterations: 0,
+        }
+    }
+
+    /// Runs `warmup_iterations` untimed iterations before each measurement.
+    pub fn warmup_iterations(mut self, warmup_iterations: u64) -> Self {
+        self.warmup_iterations = warmup_iterations;
+        self
+    }
+
+    /// Runs `iterations` timed iterations on `model`, returning the time they
+    /// took. The time includes pinning the random inputs.
+    pub fn run(
+        &mut self,
+        model: &mut DynamicVerilatedModel<'_>,
+        iterations: u64,
+    ) -> Result<Duration, DynamicVerilatedModelError> {
+        for _ in 0..self.warmup_iterations {
+            self.iteration(model)?;
+        }
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            self.iteration(model)?;
+        }
+        Ok(start.elapsed())
+    }
+
+    fn iteration(
+        &mut self,
+        model: &mut DynamicVerilatedModel<'_>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        model.randomize_inputs(&mut self.next_u64)?;
+        if model.clock_port.is_some() {
+            model.tick()
+        } else {
+            model.eval();
+            Ok(())
+        }
+    }
+}
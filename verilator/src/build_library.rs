@@ -11,7 +11,14 @@
 // - location of verilated.h
 // - verilator library is obj_dir/libverilated.a
 
-use std::{fmt::Write, fs, process::Command};
+use std::{
+    fmt::Write,
+    fs,
+    hash::{self, Hash, Hasher},
+    io::{self, BufRead, BufReader, Read},
+    process::{Command, Output, Stdio},
+    thread,
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use snafu::{Whatever, prelude::*};
@@ -62,33 +69,150 @@ fn build_ffi_for_tracing(
     Ok(())
 }
 
+/// Writes bindings for a port stored as `word_count` 32-bit words, which pin
+/// from and read into a caller-provided array of that many words, least
+/// significant first.
+fn build_ffi_for_wide_port(
+    buffer: &mut String,
+    top_module: &str,
+    port: &str,
+    word_count: usize,
+    direction: PortDirection,
+) -> Result<(), Whatever> {
+    if matches!(direction, PortDirection::Input | PortDirection::Inout) {
+        writeln!(
+            buffer,
+            r#"
+    void ffi_V{top_module}_pin_{port}(V{top_module}* top, const WData* new_value) {{
+        for (int i = 0; i < {word_count}; i++) {{
+            top->{port}[i] = new_value[i];
+        }}
+    }}
+            "#
+        )
+        .whatever_context("Failed to format wide input port FFI")?;
+    }
+
+    if matches!(direction, PortDirection::Output | PortDirection::Inout) {
+        writeln!(
+            buffer,
+            r#"
+    void ffi_V{top_module}_read_{port}(V{top_module}* top, WData* value) {{
+        for (int i = 0; i < {word_count}; i++) {{
+            value[i] = top->{port}[i];
+        }}
+    }}
+            "#
+        )
+        .whatever_context("Failed to format wide output port FFI")?;
+    }
+
+    Ok(())
+}
+
 /// Writes `extern "C"` C++ bindings for a Verilator model with the given name
 /// (`top_module`) and signature (`ports`) to the given artifact directory
 /// `artifact_directory`, returning the path to the C++ file containing the FFI
 /// wrappers.
+///
+/// Also writes a header declaring the `VL_PRINTF` replacement defined in the
+/// wrappers, which starts out suppressing all output if `quiet`.
+///
+/// If `readmem_search_paths` is nonempty, the wrappers additionally define
+/// `__wrap_fopen`, which falls back to searching those directories when a
+/// relative path opened for reading does not exist. The library must then be
+/// linked with `-Wl,--wrap=fopen`, which only GNU ld supports, so this is
+/// limited to Linux.
+///
+/// If `coverage`, the wrappers can also write out the model's coverage data,
+/// from which [`crate::saif`] reads toggle counts.
 fn build_ffi(
     artifact_directory: &Utf8Path,
     top_module: &str,
     ports: &[(&str, usize, usize, PortDirection)],
     enable_tracing: bool,
+    quiet: bool,
+    readmem_search_paths: &[Utf8PathBuf],
+    coverage: bool,
 ) -> Result<Utf8PathBuf, Whatever> {
     let ffi_wrappers = artifact_directory.join("ffi.cpp");
 
+    fs::write(
+        artifact_directory.join("printf.h"),
+        r#"#pragma once
+#ifdef __cplusplus
+extern "C"
+#endif
+int marlin_vl_printf(const char* format, ...);
+"#,
+    )
+    .whatever_context("Failed to write VL_PRINTF replacement header")?;
+
     let mut buffer = String::new();
 
     if enable_tracing {
         buffer.push_str("#include \"verilated_vcd_c.h\"\n");
         buffer.push_str("#include <stdint.h>\n");
     }
+    if coverage {
+        buffer.push_str("#include \"verilated_cov.h\"\n");
+    }
 
     writeln!(
         &mut buffer,
         r#"
 #include "verilated.h"
 #include "V{top_module}.h"
+#include <stdarg.h>
+#include <stdio.h>
+#include <string>
+
+static bool marlin_quiet = {quiet};
 
 extern "C" {{
-    void* ffi_new_V{top_module}() {{
+    int marlin_vl_printf(const char* format, ...) {{
+        if (marlin_quiet) {{
+            return 0;
+        }}
+        va_list args;
+        va_start(args, format);
+        int result = vprintf(format, args);
+        va_end(args);
+        return result;
+    }}
+
+    void ffi_marlin_set_quiet(bool quiet) {{
+        marlin_quiet = quiet;
+    }}
+
+    void* ffi_new_VerilatedContext() {{
+        return new VerilatedContext{{}};
+    }}
+
+    void ffi_delete_VerilatedContext(VerilatedContext* context) {{
+        delete context;
+    }}
+
+    uint64_t ffi_VerilatedContext_time(VerilatedContext* context) {{
+        return context->time();
+    }}
+
+    void ffi_VerilatedContext_set_time(VerilatedContext* context, uint64_t time) {{
+        context->time(time);
+    }}
+
+    void ffi_VerilatedContext_timeInc(VerilatedContext* context, uint64_t add) {{
+        context->timeInc(add);
+    }}
+
+    void ffi_VerilatedContext_traceEverOn(VerilatedContext* context, bool everOn) {{
+        context->traceEverOn(everOn);
+    }}
+
+    void* ffi_new_V{top_module}(VerilatedContext* context) {{
+        if (context) {{
+            return new V{top_module}{{context}};
+        }}
         return new V{top_module}{{}};
     }}
 
@@ -97,6 +221,29 @@ extern "C" {{
         top->eval();
     }}
 
+    void ffi_V{top_module}_final(V{top_module}* top) {{
+        top->final();
+    }}
+
+    void ffi_V{top_module}_commandArgs(V{top_module}* top, int argc, const char** argv) {{
+        // the model's own context, which may be shared with other libraries
+        top->contextp()->commandArgs(argc, argv);
+    }}
+
+    bool ffi_V{top_module}_finish(V{top_module}* top) {{
+        // report errors in `final` blocks instead of aborting the process
+        top->contextp()->fatalOnError(false);
+        top->final();
+        return top->contextp()->gotError();
+    }}
+
+    int ffi_V{top_module}_finish_code(V{top_module}* top) {{
+        if (!top->contextp()->gotFinish()) {{
+            return -1;
+        }}
+        return top->contextp()->gotError() ? 1 : 0;
+    }}
+
     void ffi_delete_V{top_module}(V{top_module}* top) {{
         delete top;
     }}
@@ -111,16 +258,66 @@ extern "C" {{
     )
     .whatever_context("Failed to format utility FFI")?;
 
+    if coverage {
+        writeln!(
+            &mut buffer,
+            r#"
+    void ffi_V{top_module}_write_coverage(V{top_module}* top, const char* path) {{
+        // the model's own context, which may be shared with other libraries
+        top->contextp()->coveragep()->write(path);
+    }}
+"#
+        )
+        .whatever_context("Failed to format coverage FFI")?;
+    }
+
+    if !readmem_search_paths.is_empty() {
+        let mut search_paths = String::new();
+        for search_path in readmem_search_paths {
+            write!(&mut search_paths, "{:?}, ", search_path.as_str())
+                .whatever_context("Failed to format readmem search path")?;
+        }
+        writeln!(
+            &mut buffer,
+            r#"
+    FILE* __real_fopen(const char* path, const char* mode);
+
+    FILE* __wrap_fopen(const char* path, const char* mode) {{
+        static const char* const search_paths[] = {{ {search_paths}}};
+        FILE* file = __real_fopen(path, mode);
+        if (file || path[0] == '/' || mode[0] != 'r') {{
+            return file;
+        }}
+        for (const char* search_path : search_paths) {{
+            std::string candidate = std::string(search_path) + "/" + path;
+            file = __real_fopen(candidate.c_str(), mode);
+            if (file) {{
+                return file;
+            }}
+        }}
+        return NULL;
+    }}
+"#
+        )
+        .whatever_context("Failed to format readmem search FFI")?;
+    }
+
     for (port, msb, lsb, direction) in ports {
-        let width = msb - lsb + 1;
+        let width = crate::port_width(*msb, *lsb);
         if width > 64 {
-            let underlying = format!(
-                "Port `{port}` on top module `{top_module}` was larger than 64 bits wide"
-            );
-            whatever!(
-                Err(underlying),
-                "We don't support larger than 64-bit width on ports yet because weird C linkage things"
-            );
+            // arrays cannot cross C linkage by value, so wide ports are
+            // copied element-wise through a pointer to their words
+            build_ffi_for_wide_port(
+                &mut buffer,
+                top_module,
+                port,
+                width.div_ceil(32),
+                *direction,
+            )
+            .whatever_context(format!(
+                "Failed to format wide port FFI for `{port}`"
+            ))?;
+            continue;
         }
         let macro_prefix = match direction {
             PortDirection::Input => "VL_IN",
@@ -133,26 +330,17 @@ extern "C" {{
             "16"
         } else if width <= 32 {
             ""
-        } else if width <= 64 {
-            "64"
         } else {
-            "W"
+            "64"
         };
         let type_macro = |name: Option<&str>| {
             format!(
-                "{}{}({}, {}, {}{})",
+                "{}{}({}, {}, {})",
                 macro_prefix,
                 macro_suffix,
                 name.unwrap_or("/* return value */"),
                 msb,
                 lsb,
-                if width > 64 {
-                    format!(", {}", width.div_ceil(32)) // words are 32 bits
-                // according to header
-                // file
-                } else {
-                    "".into()
-                }
             )
         };
 
@@ -198,34 +386,130 @@ extern "C" {{
     Ok(ffi_wrappers)
 }
 
-/// Sets up the DPI artifacts directory and generates DPI function bindings if
-/// needed, returning:
-/// 1. `Some` DPI bindings file to compile in (or `None` if there are no DPI
-///    functions in the first place)
-/// 2. Whether there was a regeneration of any kind
+fn library_name(top_module: &str) -> String {
+    format!("marlin_V{top_module}")
+}
+
+/// The path of the dynamic library [`build_library`] builds for `top_module`
+/// in `artifact_directory`.
 ///
-/// This function is a nop if `dpi_functions.is_empty()`.
-fn bind_dpi_if_needed(
+/// Verilator names the library created by `--lib-create` with a `.so` suffix
+/// on every platform, including macOS, where it is loaded just the same, so
+/// the suffix is deliberately not [`std::env::consts::DLL_SUFFIX`].
+pub(crate) fn library_path(
+    artifact_directory: &Utf8Path,
+    top_module: &str,
+) -> Utf8PathBuf {
+    artifact_directory
+        .join("obj_dir")
+        .join(format!("lib{}.so", library_name(top_module)))
+}
+
+/// The path to which [`build_library`] copies the dynamic library for
+/// `top_module` in `cache_directory`. Unlike [`library_path`], no
+/// intermediate build products are kept alongside it.
+pub(crate) fn cached_library_path(
+    cache_directory: &Utf8Path,
     top_module: &str,
+) -> Utf8PathBuf {
+    cache_directory.join(format!("lib{}.so", library_name(top_module)))
+}
+
+/// Whether the library cached in `cache_directory` was built from sources with
+/// the hash `source_hash` (see [`source_hash`]) and with the same DPI bindings.
+fn is_cache_fresh(
+    source_hash: u64,
     dpi_functions: &[&'static dyn DpiFunction],
-    dpi_artifact_directory: &Utf8Path,
-    verbose: bool,
-) -> Result<(Option<Utf8PathBuf>, bool), Whatever> {
+    top_module: &str,
+    cache_directory: &Utf8Path,
+) -> Result<bool, Whatever> {
+    if needs_verilator_rebuild(
+        source_hash,
+        &cached_library_path(cache_directory, top_module),
+    )? {
+        return Ok(false);
+    }
+
+    let cached_dpi_code = fs::read_to_string(cache_directory.join("dpi.cpp"));
+    Ok(if dpi_functions.is_empty() {
+        cached_dpi_code.is_err()
+    } else {
+        cached_dpi_code.is_ok_and(|cached_dpi_code| {
+            cached_dpi_code == dpi_c_code(top_module, dpi_functions)
+        })
+    })
+}
+
+/// Copies the freshly built library at `library_path` into `cache_directory`,
+/// along with the hash of its sources and the DPI bindings it was built with,
+/// returning the path of the copy.
+fn store_in_cache(
+    library_path: &Utf8Path,
+    dpi_functions: &[&'static dyn DpiFunction],
+    top_module: &str,
+    cache_directory: &Utf8Path,
+) -> Result<Utf8PathBuf, Whatever> {
+    fs::create_dir_all(cache_directory).whatever_context(format!(
+        "Failed to create cache directory {cache_directory}"
+    ))?;
+
+    // another process may have the previous library loaded, so replace it
+    // atomically instead of overwriting it in place
+    let cached_library_path = cached_library_path(cache_directory, top_module);
+    let partial_library_path = cached_library_path.with_extension("so.partial");
+    fs::copy(library_path, &partial_library_path).whatever_context(format!(
+        "Failed to copy dynamic library into cache directory {cache_directory}"
+    ))?;
+    fs::rename(&partial_library_path, &cached_library_path).whatever_context(
+        format!(
+            "Failed to move dynamic library into place at {cached_library_path}"
+        ),
+    )?;
+    fs::copy(
+        source_hash_path(library_path),
+        source_hash_path(&cached_library_path),
+    )
+    .whatever_context(format!(
+        "Failed to copy source hash into cache directory {cache_directory}"
+    ))?;
+
+    let cached_dpi_path = cache_directory.join("dpi.cpp");
     if dpi_functions.is_empty() {
-        return Ok((None, false));
+        let _ = fs::remove_file(cached_dpi_path);
+    } else {
+        fs::write(&cached_dpi_path, dpi_c_code(top_module, dpi_functions))
+            .whatever_context(format!(
+                "Failed to write DPI bindings to {cached_dpi_path}"
+            ))?;
     }
 
-    let dpi_file_absolute_path = dpi_artifact_directory.join("dpi.cpp");
-    // TODO: hard-coded knowledge, same verilator bug
-    let dpi_file = Utf8PathBuf::from("../dpi/dpi.cpp");
+    Ok(cached_library_path)
+}
 
-    let file_code = format!(
+/// Wraps `c_code` generated for `dpi_function` in its
+/// [`DpiFunction::c_guard`], if any.
+fn guard_c_code(dpi_function: &dyn DpiFunction, c_code: String) -> String {
+    match dpi_function.c_guard() {
+        Some(guard) => format!("#if {guard}\n{c_code}\n#endif"),
+        None => c_code,
+    }
+}
+
+/// The C++ code binding `dpi_functions` for the Verilog module `top_module`,
+/// which is compiled into the library as `dpi.cpp`.
+pub(crate) fn dpi_c_code(
+    top_module: &str,
+    dpi_functions: &[&'static dyn DpiFunction],
+) -> String {
+    format!(
         "#include \"svdpi.h\"
 #include \"V{}__Dpi.h\"
 #include <stdint.h>
+#include <string.h>
 {}
-extern \"C\" void dpi_init_callback(void** callbacks) {{
+extern \"C\" bool dpi_init_callback(const char* name, void* callback) {{
 {}
+    return false;
 }}",
         top_module,
         dpi_functions
@@ -246,36 +530,70 @@ extern \"C\" void dpi_init_callback(void** callbacks) {{
                     .join(",");
                 let return_type = dpi_function.return_type();
 
-                format!(
-                    "static {return_type} (*rust_{name})({parameters});
+                guard_c_code(
+                    *dpi_function,
+                    format!(
+                        "static {return_type} (*rust_{name})({parameters});
 {return_type} {name}({parameters}) {{
     return rust_{name}({arguments});
 }}"
+                    ),
                 )
             })
             .collect::<Vec<_>>()
             .join("\n"),
         dpi_functions
             .iter()
-            .enumerate()
-            .map(|(i, dpi_function)| {
+            .map(|dpi_function| {
+                let name = dpi_function.name();
                 let parameters = dpi_function
                     .parameters()
                     .iter()
                     .map(|(name, ty)| format!("{ty} {name}"))
                     .collect::<Vec<_>>()
                     .join(", ");
+                // the name is matched even when the function is guarded out
+                // so that the runtime does not mistake it for a stale library
                 format!(
-                    "   rust_{} = ( {}(*)({}) ) callbacks[{}];",
-                    dpi_function.name(),
-                    dpi_function.return_type(),
-                    parameters,
-                    i
+                    "    if (strcmp(name, \"{name}\") == 0) {{
+{}
+        return true;
+    }}",
+                    guard_c_code(
+                        *dpi_function,
+                        format!(
+                            "        rust_{name} = ( {}(*)({parameters}) ) callback;",
+                            dpi_function.return_type(),
+                        ),
+                    )
                 )
             })
             .collect::<Vec<_>>()
             .join("\n"),
-    );
+    )
+}
+
+/// Sets up the DPI artifacts directory and generates DPI function bindings if
+/// needed, returning:
+/// 1. `Some` DPI bindings file to compile in (or `None` if there are no DPI
+///    functions in the first place)
+/// 2. Whether there was a regeneration of any kind
+///
+/// This function is a nop if `dpi_functions.is_empty()`.
+fn bind_dpi_if_needed(
+    top_module: &str,
+    dpi_functions: &[&'static dyn DpiFunction],
+    dpi_artifact_directory: &Utf8Path,
+    verbose: bool,
+) -> Result<(Option<Utf8PathBuf>, bool), Whatever> {
+    if dpi_functions.is_empty() {
+        return Ok((None, false));
+    }
+
+    let dpi_file_absolute_path = dpi_artifact_directory.join("dpi.cpp");
+    let dpi_file = Utf8PathBuf::from(DPI_FILE);
+
+    let file_code = dpi_c_code(top_module, dpi_functions);
 
     // only rebuild if there's been a change
     if fs::read_to_string(&dpi_file_absolute_path)
@@ -300,40 +618,68 @@ extern \"C\" void dpi_init_callback(void** callbacks) {{
     Ok((Some(dpi_file), true))
 }
 
-/// Returns `Ok(true)` when the library doesn't exist or if any Verilog source
-/// file has been modified after last building the library.
-fn needs_verilator_rebuild(
+// TODO: hard-coded knowledge, same verilator bug as the FFI wrappers
+/// The path of the DPI bindings as passed to Verilator, relative to `--Mdir`.
+pub(crate) const DPI_FILE: &str = "../dpi/dpi.cpp";
+
+/// The path of the file next to the library at `library_path` recording the
+/// [`source_hash`] of the sources it was built from.
+fn source_hash_path(library_path: &Utf8Path) -> Utf8PathBuf {
+    format!("{library_path}.sources").into()
+}
+
+/// Hashes the paths and contents of `source_files` and of the files directly
+/// inside `include_directories`. Unlike modification times, the hash does not
+/// change when, e.g., `git checkout` touches a file without editing it.
+///
+/// The hash is stable within a build of Marlin, so upgrading may rebuild
+/// libraries once.
+pub(crate) fn source_hash(
     source_files: &[Utf8PathBuf],
+    include_directories: &[Utf8PathBuf],
+) -> Result<u64, Whatever> {
+    let mut files = source_files.to_vec();
+    for include_directory in include_directories {
+        // Verilator tolerates missing include directories, so we do too
+        let Ok(entries) = include_directory.read_dir_utf8() else {
+            continue;
+        };
+        let mut included_files = vec![];
+        for entry in entries {
+            let entry = entry.whatever_context(format!(
+                "Failed to read include directory {include_directory}"
+            ))?;
+            if entry.path().is_file() {
+                included_files.push(entry.into_path());
+            }
+        }
+        // the order of directory entries is unspecified
+        included_files.sort();
+        files.extend(included_files);
+    }
+
+    let mut hasher = hash::DefaultHasher::new();
+    for file in files {
+        file.hash(&mut hasher);
+        fs::read(&file)
+            .whatever_context(format!("Failed to read source file {file}"))?
+            .hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+/// Returns `Ok(true)` when the library doesn't exist or was not built from
+/// sources with the hash `source_hash` (see [`source_hash`]).
+fn needs_verilator_rebuild(
+    source_hash: u64,
     library_path: &Utf8Path,
 ) -> Result<bool, Whatever> {
     if !library_path.exists() {
         return Ok(true);
     }
 
-    let last_built = fs::metadata(library_path)
-        .whatever_context(format!(
-            "Failed to read file metadata for dynamic library {library_path}"
-        ))?
-        .modified()
-        .whatever_context(format!(
-            "Failed to determine last-modified time for dynamic library {library_path}"
-        ))?;
-
-    for source_file in source_files {
-        let last_edited = fs::metadata(source_file)
-            .whatever_context(format!(
-                "Failed to read file metadata for source file {source_file}"
-            ))?
-            .modified()
-            .whatever_context(format!(
-                "Failed to determine last-modified time for source file {source_file}"
-            ))?;
-        if last_edited > last_built {
-            return Ok(true);
-        }
-    }
-
-    Ok(false)
+    Ok(fs::read_to_string(source_hash_path(library_path))
+        .map_or(true, |built_hash| built_hash != source_hash.to_string()))
 }
 
 /// Builds a dynamic library using Verilator serving as the runtime for the
@@ -353,31 +699,65 @@ fn needs_verilator_rebuild(
 /// [`build_dpi_if_needed`]). It is important that this function is a nop when
 /// there no DPI functions because invoking `rustc` takes a long time.
 ///
-/// Then, if the DPI files were rebuilt, any Verilog source code has been
-/// edited, or the `options` force rebuilding, we proceed in (re)building the
+/// Then, if the DPI files were rebuilt, the contents of any Verilog source code
+/// (or file in an include directory) changed, or the `options` force
+/// rebuilding, we proceed in (re)building the
 /// dynamic library. Otherwise, the function returns the library path
 /// immediately here.
 ///
 /// Next, the FFI wrappers are rebuilt (although we could probably be smarter
 /// about this and only rebuild if the module's source file was edited).
 ///
-/// Finally, we invoke `verilator` and return the library path as well as
-/// whether the library was rebuilt.
+/// Finally, we invoke `verilator` and return the library path, whether the
+/// library was rebuilt, and the `%Warning` lines Verilator printed if it was.
+///
+/// If a `cache_directory` is given, the library is copied there after it is
+/// built and loaded from there, and an up-to-date library in the cache is
+/// returned without consulting the (possibly missing) artifacts at all. This
+/// way, only the cache directory needs to persist, e.g., between CI runs.
 ///
 /// This function is not thread-safe; the `artifact_directory` must be guarded.
 #[allow(clippy::too_many_arguments)]
 pub fn build_library(
     source_files: &[Utf8PathBuf],
+    file_list_sources: &[Utf8PathBuf],
     include_directories: &[Utf8PathBuf],
     dpi_functions: &[&'static dyn DpiFunction],
     top_module: &str,
     ports: &[(&str, usize, usize, PortDirection)],
     artifact_directory: &Utf8Path,
+    cache_directory: Option<&Utf8Path>,
     options: &VerilatorRuntimeOptions,
     config: &VerilatedModelConfig,
     verbose: bool,
     on_rebuild: impl FnOnce() -> Result<(), Whatever>,
-) -> Result<(Utf8PathBuf, bool), Whatever> {
+) -> Result<(Utf8PathBuf, bool, Vec<String>), Whatever> {
+    let all_source_files =
+        [source_files, file_list_sources, &options.file_lists].concat();
+    let source_hash = source_hash(&all_source_files, include_directories)
+        .whatever_context("Failed to hash source files")?;
+
+    if let Some(cache_directory) = cache_directory {
+        if !options.force_verilator_rebuild
+            && is_cache_fresh(
+                source_hash,
+                dpi_functions,
+                top_module,
+                cache_directory,
+            )
+            .whatever_context("Failed to check if the cache is up to date")?
+        {
+            if verbose {
+                log::info!("| Using cached library in {cache_directory}");
+            }
+            return Ok((
+                cached_library_path(cache_directory, top_module),
+                false,
+                vec![],
+            ));
+        }
+    }
+
     if verbose {
         log::info!("| Preparing artifacts directory");
     }
@@ -391,9 +771,7 @@ pub fn build_library(
     fs::create_dir_all(&dpi_artifact_directory).whatever_context(
         "Failed to create dpi/ subdirectory under artifacts directory",
     )?;
-    let library_name = format!("marlin_V{top_module}");
-    let library_path =
-        verilator_artifact_directory.join(format!("lib{library_name}.so"));
+    let library_path = library_path(artifact_directory, top_module);
 
     let (dpi_file, dpi_rebuilt) = bind_dpi_if_needed(
         top_module,
@@ -404,11 +782,8 @@ pub fn build_library(
     .whatever_context("Failed to build DPI functions")?;
 
     if !options.force_verilator_rebuild
-        && (!needs_verilator_rebuild(
-            source_files,
-            &verilator_artifact_directory,
-        )
-        .whatever_context("Failed to check if artifacts need rebuilding")?
+        && (!needs_verilator_rebuild(source_hash, &library_path)
+            .whatever_context("Failed to check if artifacts need rebuilding")?
             && !dpi_rebuilt)
     {
         if verbose {
@@ -416,23 +791,158 @@ pub fn build_library(
                 "| Skipping rebuild of verilated model due to no changes"
             );
         }
-        return Ok((library_path, false));
+        // the cache may have been cleared even if the artifacts are fine
+        let library_path = match cache_directory {
+            Some(cache_directory) => store_in_cache(
+                &library_path,
+                dpi_functions,
+                top_module,
+                cache_directory,
+            )?,
+            None => library_path,
+        };
+        return Ok((library_path, false, vec![]));
     }
 
     on_rebuild()?;
 
+    let mut readmem_search_paths = vec![];
+    for search_path in &config.readmem_search_paths {
+        readmem_search_paths.push(
+            search_path.canonicalize_utf8().whatever_context(format!(
+                "Failed to resolve readmem search path {search_path}"
+            ))?,
+        );
+    }
+
     let _ffi_wrappers = build_ffi(
         &ffi_artifact_directory,
         top_module,
         ports,
         config.enable_tracing,
+        config.quiet,
+        &readmem_search_paths,
+        config.coverage.any(),
     )
     .whatever_context("Failed to build FFI wrappers")?;
 
+    let mut verilator_command = Command::new(&options.verilator_executable);
+    if let Some(verilator_root) = &options.verilator_root {
+        if !verilator_root.join("include/verilated.h").is_file() {
+            whatever!(
+                "Could not locate verilated.h under the configured Verilator root {verilator_root}: expected it at {}",
+                verilator_root.join("include/verilated.h")
+            );
+        }
+        verilator_command.env("VERILATOR_ROOT", verilator_root);
+    }
+    verilator_command.args(verilator_arguments(
+        source_files,
+        include_directories,
+        dpi_file.as_deref(),
+        top_module,
+        &verilator_artifact_directory,
+        options,
+        config,
+    )?);
+    if verbose {
+        log::info!("| Verilator invocation: {:?}", verilator_command);
+    }
+    let verilator_output = if options.stream_build_output {
+        output_streaming(&mut verilator_command)
+    } else {
+        verilator_command.output()
+    }
+    .whatever_context("Invocation of Verilator failed")?;
+
+    if !verilator_output.status.success() {
+        let stderr =
+            String::from_utf8(verilator_output.stderr).unwrap_or_default();
+        let hint = if stderr.contains("verilated.h: No such file") {
+            "\n\nThe C++ compiler could not locate verilated.h: try setting `verilator_root` in the runtime options to the root of your Verilator installation"
+        } else {
+            ""
+        };
+
+        // relative paths like `../ffi/ffi.cpp` are resolved against `--Mdir`,
+        // so the artifact directory is needed to reproduce the invocation
+        whatever!(
+            "Invocation of verilator failed with nonzero exit code {}{}\n\n--- COMMAND ---\n{:?}\n\n--- ARTIFACT DIRECTORY ---\n{}\n\n--- WORKING DIRECTORY ---\n{}\n\n--- STDOUT ---\n{}\n\n--- STDERR ---\n{}",
+            verilator_output.status,
+            hint,
+            verilator_command,
+            artifact_directory,
+            std::env::current_dir()
+                .map(|directory| directory.display().to_string())
+                .unwrap_or_else(|_| "<unknown>".into()),
+            String::from_utf8(verilator_output.stdout).unwrap_or_default(),
+            stderr
+        );
+    }
+
+    let output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&verilator_output.stdout),
+        String::from_utf8_lossy(&verilator_output.stderr)
+    );
+    let warnings = output
+        .lines()
+        .filter(|line| line.starts_with("%Warning"))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if verbose {
+        for warning in &warnings {
+            log::warn!("| {warning}");
+        }
+    }
+    if options.treat_warnings_as_errors && !warnings.is_empty() {
+        // otherwise the next build would consider the library up to date
+        let _ = fs::remove_file(&library_path);
+        whatever!(
+            "Verilator emitted warnings, which are treated as errors:\n{}",
+            warnings.join("\n")
+        );
+    }
+
+    let source_hash_path = source_hash_path(&library_path);
+    fs::write(&source_hash_path, source_hash.to_string()).whatever_context(
+        format!("Failed to write source hash to {source_hash_path}"),
+    )?;
+
+    let library_path = match cache_directory {
+        Some(cache_directory) => store_in_cache(
+            &library_path,
+            dpi_functions,
+            top_module,
+            cache_directory,
+        )?,
+        None => library_path,
+    };
+
+    Ok((library_path, true, warnings))
+}
+
+/// The arguments Verilator is invoked with to build a library for
+/// `top_module`, not including the executable itself. Relative paths, like
+/// those of the FFI wrappers and `dpi_file`, are resolved against
+/// `verilator_artifact_directory`, which is passed as `--Mdir`.
+pub(crate) fn verilator_arguments(
+    source_files: &[Utf8PathBuf],
+    include_directories: &[Utf8PathBuf],
+    dpi_file: Option<&Utf8Path>,
+    top_module: &str,
+    verilator_artifact_directory: &Utf8Path,
+    options: &VerilatorRuntimeOptions,
+    config: &VerilatedModelConfig,
+) -> Result<Vec<String>, Whatever> {
     // bug in verilator#5226 means the directory must be relative to -Mdir
     let ffi_wrappers = Utf8Path::new("../ffi/ffi.cpp");
 
-    let mut cflags = "-shared -fpic".to_string();
+    // route Verilator's printing (e.g., `$display`) through `marlin_vl_printf`
+    // so it can be silenced at runtime, with the same relative path caveat
+    let mut cflags =
+        "-shared -fpic -DVL_PRINTF=marlin_vl_printf -include ../ffi/printf.h"
+            .to_string();
     if let Some(cxx_standard) = config.cxx_standard {
         cflags += " -std=";
         cflags += match cxx_standard {
@@ -445,51 +955,158 @@ pub fn build_library(
             crate::CxxStandard::Cxx26 => "c++26",
         };
     }
+    if config.enable_lto {
+        cflags += " -flto";
+    }
 
-    let mut verilator_command = Command::new(&options.verilator_executable);
-    verilator_command
-        .args(["--cc", "-sv", "-j", "0", "--build"])
-        .args(["-CFLAGS", &cflags])
-        .args(["--lib-create", &library_name])
-        .args(["--Mdir", verilator_artifact_directory.as_str()])
-        .args(["--top-module", top_module])
-        .args(source_files)
-        .arg(ffi_wrappers);
+    let mut arguments: Vec<String> = vec![];
+    arguments.extend(["--cc", "-sv", "-j", "0", "--build"].map(String::from));
+    arguments.extend(["-CFLAGS".into(), cflags]);
+    arguments.extend(["--lib-create".into(), library_name(top_module)]);
+    arguments
+        .extend(["--Mdir".into(), verilator_artifact_directory.to_string()]);
+    arguments.extend(["--top-module".into(), top_module.to_string()]);
+    arguments.extend(source_files.iter().map(Utf8PathBuf::to_string));
+    arguments.push(ffi_wrappers.to_string());
+    for file_list in &options.file_lists {
+        arguments.extend(["-f".into(), file_list.to_string()]);
+    }
+    // the FFI wrappers assume C++ output, so override any `--sc` in the file
+    // lists (later options take precedence). The `--pins-*` options only
+    // affect SystemC output, so they need no overriding.
+    arguments.push("--cc".into());
     for include_directory in include_directories {
-        verilator_command.arg(format!("-I{include_directory}"));
+        arguments.push(format!("-I{include_directory}"));
     }
     if let Some(dpi_file) = dpi_file {
-        verilator_command.arg(dpi_file);
+        arguments.push(dpi_file.to_string());
     }
     if config.verilator_optimization != 0 {
         let level = config.verilator_optimization;
         if (1..=3).contains(&level) {
-            verilator_command.arg(format!("-O{level}"));
+            arguments.push(format!("-O{level}"));
         } else {
             whatever!("Invalid Verilator optimization level: {}", level);
         }
     }
+    if let Some(unroll_count) = config.unroll_count {
+        arguments.extend(["--unroll-count".into(), unroll_count.to_string()]);
+    }
+    for (name, value) in &config.parameters {
+        arguments.push(format!("-G{name}={value}"));
+    }
+    for (name, value) in &config.defines {
+        match value {
+            Some(value) => arguments.push(format!("+define+{name}={value}")),
+            None => arguments.push(format!("+define+{name}")),
+        }
+    }
+    // warnings would otherwise fail the build before they can be collected;
+    // `treat_warnings_as_errors` turns them back into an error afterward
+    arguments.push("-Wno-fatal".into());
     for ignored_warning in &config.ignored_warnings {
-        verilator_command.arg(format!("-Wno-{ignored_warning}"));
+        arguments.push(format!("-Wno-{ignored_warning}"));
+    }
+    if config.relative_includes {
+        arguments.push("--relative-includes".into());
     }
     if config.enable_tracing {
-        verilator_command.arg("--trace");
+        arguments.push("--trace".into());
     }
-    if verbose {
-        log::info!("| Verilator invocation: {:?}", verilator_command);
+    arguments.extend(config.coverage.verilator_flags().map(String::from));
+    if config.enable_lto {
+        arguments.extend(["-LDFLAGS", "-flto"].map(String::from));
     }
-    let verilator_output = verilator_command
-        .output()
-        .whatever_context("Invocation of Verilator failed")?;
+    // redirect the model's `fopen` calls, like those made by `$readmemh`, to
+    // the `__wrap_fopen` search fallback in the FFI wrappers. Only GNU ld
+    // supports `--wrap`, so the search is limited to Linux.
+    if !config.readmem_search_paths.is_empty() {
+        if !cfg!(target_os = "linux") {
+            whatever!(
+                "`readmem_search_paths` is only supported on Linux, since it relies on the GNU linker"
+            );
+        }
+        arguments.extend(["-LDFLAGS", "-Wl,--wrap=fopen"].map(String::from));
+    }
+    // last so that they can override the options above, except for the
+    // output language, which the FFI wrappers depend on
+    if let Some(argument) = config
+        .extra_verilator_args
+        .iter()
+        .find(|argument| matches!(argument.as_str(), "--sc" | "-sc"))
+    {
+        whatever!(
+            "Extra Verilator argument `{}` is not supported, since Marlin requires C++ output",
+            argument
+        );
+    }
+    arguments.extend(config.extra_verilator_args.iter().cloned());
 
-    if !verilator_output.status.success() {
+    Ok(arguments)
+}
+
+/// The first line Verilator prints for `--version`, e.g., `Verilator 5.030
+/// 2024-10-27`.
+pub(crate) fn verilator_version(
+    options: &VerilatorRuntimeOptions,
+) -> Result<String, Whatever> {
+    let mut verilator_command = Command::new(&options.verilator_executable);
+    if let Some(verilator_root) = &options.verilator_root {
+        verilator_command.env("VERILATOR_ROOT", verilator_root);
+    }
+    let output = verilator_command
+        .arg("--version")
+        .output()
+        .whatever_context("Failed to query the Verilator version")?;
+    if !output.status.success() {
         whatever!(
-            "Invocation of verilator failed with nonzero exit code {}\n\n--- STDOUT ---\n{}\n\n--- STDERR ---\n{}",
-            verilator_output.status,
-            String::from_utf8(verilator_output.stdout).unwrap_or_default(),
-            String::from_utf8(verilator_output.stderr).unwrap_or_default()
+            "Querying the Verilator version failed with nonzero exit code {}",
+            output.status
         );
     }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+/// Runs `command` like [`Command::output`], but also forwards everything it
+/// prints to the (non-captured) standard error line by line as it runs.
+fn output_streaming(command: &mut Command) -> io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let child_stdout = child.stdout.take().expect("stdout is piped");
+    let child_stderr = child.stderr.take().expect("stderr is piped");
+
+    let (stdout, stderr) = thread::scope(|scope| {
+        let stdout = scope.spawn(|| forward_lines(child_stdout));
+        let stderr = forward_lines(child_stderr);
+        (stdout.join().unwrap_or_default(), stderr)
+    });
 
-    Ok((library_path, true))
+    Ok(Output {
+        status: child.wait()?,
+        stdout,
+        stderr,
+    })
+}
+
+/// Reads `stream` to the end, forwarding each line to the (non-captured)
+/// standard error and returning everything read.
+fn forward_lines(stream: impl Read) -> Vec<u8> {
+    let mut reader = BufReader::new(stream);
+    let mut captured = vec![];
+    let mut line = vec![];
+    while reader
+        .read_until(b'\n', &mut line)
+        .is_ok_and(|read| read > 0)
+    {
+        let _ = io::Write::write_all(&mut io::stderr().lock(), &line);
+        captured.append(&mut line);
+    }
+    captured
 }
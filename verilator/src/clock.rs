@@ -0,0 +1,179 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Declarative clock generation for designs with one or more clock domains.
+//!
+//! ```no_run
+//! # use marlin_verilator::*;
+//! # use marlin_verilator::clock::*;
+//! # use snafu::ResultExt;
+//! # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+//! # || -> Result<(), snafu::Whatever> {
+//! # let mut model = runtime.create_dyn_model("main", "src/main.sv", &[], VerilatedModelConfig::default())?;
+//! let mut scheduler = ClockScheduler::new([
+//!     ClockSpec {
+//!         port: "fast_clk".into(),
+//!         period_ps: 1_000,
+//!         phase_ps: 0,
+//!     },
+//!     ClockSpec {
+//!         port: "slow_clk".into(),
+//!         period_ps: 3_000,
+//!         phase_ps: 500,
+//!     },
+//! ])?;
+//! scheduler
+//!     .advance(&mut model, 10_000)
+//!     .whatever_context("Failed to drive clocks")?;
+//! # Ok(()) };
+//! ```
+
+use snafu::{Whatever, whatever};
+
+use crate::{
+    AsDynamicVerilatedModel,
+    dynamic::{DynamicVerilatedModelError, VerilatorValue},
+    vcd::Vcd,
+};
+
+/// A free-running clock driving a 1-bit input port. The clock starts low and
+/// rises for the first time at `phase_ps`, toggling every half period
+/// afterward.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClockSpec {
+    /// The name of the input port the clock drives.
+    pub port: String,
+
+    /// The period of the clock in picoseconds. This must be nonzero and even so
+    /// that each half period is a whole number of picoseconds.
+    pub period_ps: u64,
+
+    /// The time of the first rising edge in picoseconds.
+    pub phase_ps: u64,
+}
+
+struct ClockState {
+    spec: ClockSpec,
+    level: bool,
+    next_edge_ps: u64,
+}
+
+/// Drives a set of [`ClockSpec`]s on a model, evaluating the model at every
+/// clock edge. Simultaneous edges on different clocks are applied together
+/// before a single evaluation.
+pub struct ClockScheduler {
+    clocks: Vec<ClockState>,
+    time_ps: u64,
+    started: bool,
+}
+
+impl ClockScheduler {
+    /// Creates a scheduler starting at time zero. Fails if any clock has an
+    /// invalid period or if two clocks drive the same port.
+    pub fn new(
+        clocks: impl IntoIterator<Item = ClockSpec>,
+    ) -> Result<Self, Whatever> {
+        let mut states: Vec<ClockState> = vec![];
+        for spec in clocks {
+            if spec.period_ps == 0 || spec.period_ps % 2 != 0 {
+                whatever!(
+                    "Clock on port {} has period {}ps, but the period must be nonzero and even",
+                    spec.port,
+                    spec.period_ps
+                );
+            }
+            if states.iter().any(|state| state.spec.port == spec.port) {
+                whatever!("Multiple clocks drive port {}", spec.port);
+            }
+            states.push(ClockState {
+                next_edge_ps: spec.phase_ps,
+                spec,
+                level: false,
+            });
+        }
+
+        Ok(Self {
+            clocks: states,
+            time_ps: 0,
+            started: false,
+        })
+    }
+
+    /// The current simulation time in picoseconds.
+    pub fn time_ps(&self) -> u64 {
+        self.time_ps
+    }
+
+    /// Advances simulation time by `duration_ps`, toggling each clock at its
+    /// edges within `[time_ps, time_ps + duration_ps)` and evaluating `model`
+    /// after each set of edges.
+    ///
+    /// On the first call, every clock is driven low and the model evaluated
+    /// before any edges occur.
+    pub fn advance<'ctx>(
+        &mut self,
+        model: &mut impl AsDynamicVerilatedModel<'ctx>,
+        duration_ps: u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        self.advance_with(model, duration_ps, |model| model, |_, _| {})
+    }
+
+    /// Like [`ClockScheduler::advance`], but evaluates the model traced by
+    /// `vcd` and dumps its state at the time of each evaluation.
+    pub fn advance_traced<'ctx, M: AsDynamicVerilatedModel<'ctx>>(
+        &mut self,
+        vcd: &mut Vcd<'_, M>,
+        duration_ps: u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        self.advance_with(vcd, duration_ps, |vcd| &mut **vcd, Vcd::dump)
+    }
+
+    fn advance_with<'ctx, T, M: AsDynamicVerilatedModel<'ctx>>(
+        &mut self,
+        target: &mut T,
+        duration_ps: u64,
+        model: impl Fn(&mut T) -> &mut M,
+        mut dump: impl FnMut(&mut T, u64),
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let end_ps = self.time_ps + duration_ps;
+
+        if !self.started {
+            for clock in &self.clocks {
+                model(target)
+                    .pin(clock.spec.port.as_str(), VerilatorValue::CData(0))?;
+            }
+            model(target).eval();
+            if !self.clocks.iter().any(|clock| clock.next_edge_ps == 0) {
+                dump(target, self.time_ps);
+            }
+            self.started = true;
+        }
+
+        while let Some(edge_ps) = self
+            .clocks
+            .iter()
+            .map(|clock| clock.next_edge_ps)
+            .min()
+            .filter(|edge_ps| *edge_ps < end_ps)
+        {
+            for clock in &mut self.clocks {
+                if clock.next_edge_ps == edge_ps {
+                    clock.level = !clock.level;
+                    clock.next_edge_ps += clock.spec.period_ps / 2;
+                    model(target).pin(
+                        clock.spec.port.as_str(),
+                        VerilatorValue::CData(clock.level as u8),
+                    )?;
+                }
+            }
+            model(target).eval();
+            dump(target, edge_ps);
+        }
+
+        self.time_ps = end_ps;
+        Ok(())
+    }
+}
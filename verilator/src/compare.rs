@@ -0,0 +1,223 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Differential testing of two models with the same interface, e.g., an
+//! optimized design against a reference one, and golden testing of a model
+//! against fixed test vectors ([`run_vectors`]).
+//!
+//! ```no_run
+//! # use marlin_verilator::*;
+//! # use marlin_verilator::compare::*;
+//! # use snafu::ResultExt;
+//! # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+//! # || -> Result<(), snafu::Whatever> {
+//! # let ports = &[];
+//! let mut reference = runtime.create_dyn_model(
+//!     "adder", "src/adder.sv", ports, VerilatedModelConfig::default()
+//! )?;
+//! let mut optimized = runtime.create_dyn_model(
+//!     "fast_adder", "src/fast_adder.sv", ports, VerilatedModelConfig::default()
+//! )?;
+//! compare_models(
+//!     &mut reference,
+//!     &mut optimized,
+//!     |cycle| vec![("a", (cycle as u32).into()), ("b", 1u32.into())],
+//!     &["sum"],
+//!     1000,
+//! )
+//! .whatever_context("Adders diverged")?;
+//! # Ok(()) };
+//! ```
+
+use snafu::{ResultExt, Snafu};
+
+use crate::{
+    AsDynamicVerilatedModel,
+    dynamic::{DynamicVerilatedModelError, VerilatorValue},
+};
+
+/// The reason [`compare_models`] stopped.
+#[derive(Debug, Snafu)]
+pub enum Divergence {
+    #[snafu(display(
+        "Models diverged on cycle {cycle}: port {port} was {a} on the first model but {b} on the second"
+    ))]
+    Mismatch {
+        cycle: usize,
+        port: String,
+        a: VerilatorValue,
+        b: VerilatorValue,
+    },
+    #[snafu(display("Failed to access a port on cycle {cycle}"))]
+    PortAccess {
+        cycle: usize,
+        source: DynamicVerilatedModelError,
+    },
+}
+
+/// For each of `cycles` cycles, pins the values `inputs(cycle)` on both `a` and
+/// `b`, evaluates both, and then checks that every port in `outputs` reads the
+/// same on both models. Returns the first difference found, checking `outputs`
+/// in order.
+///
+/// To ignore a port, leave it out of `outputs`. To ignore only some of its
+/// bits, see [`compare_models_masked`].
+pub fn compare_models<'a, 'b, 'port>(
+    a: &mut impl AsDynamicVerilatedModel<'a>,
+    b: &mut impl AsDynamicVerilatedModel<'b>,
+    inputs: impl FnMut(usize) -> Vec<(&'port str, VerilatorValue)>,
+    outputs: &[&str],
+    cycles: usize,
+) -> Result<(), Divergence> {
+    let outputs = outputs
+        .iter()
+        .map(|port| (*port, u64::MAX))
+        .collect::<Vec<_>>();
+    compare_models_masked(a, b, inputs, &outputs, cycles)
+}
+
+/// Like [`compare_models`], but each port in `outputs` comes with a mask, and
+/// the models only diverge if a port differs in a bit set in its mask. Clear
+/// bits are don't-cares, e.g., for bits that are legitimately uninitialized.
+/// The mask covers the low 64 bits of a port, and any higher bits of a wider
+/// port are always compared.
+pub fn compare_models_masked<'a, 'b, 'port>(
+    a: &mut impl AsDynamicVerilatedModel<'a>,
+    b: &mut impl AsDynamicVerilatedModel<'b>,
+    mut inputs: impl FnMut(usize) -> Vec<(&'port str, VerilatorValue)>,
+    outputs: &[(&str, u64)],
+    cycles: usize,
+) -> Result<(), Divergence> {
+    for cycle in 0..cycles {
+        for (port, value) in inputs(cycle) {
+            a.pin(port, value.clone())
+                .context(PortAccessSnafu { cycle })?;
+            b.pin(port, value).context(PortAccessSnafu { cycle })?;
+        }
+        a.eval();
+        b.eval();
+
+        for (port, mask) in outputs {
+            let a_value = a.read(*port).context(PortAccessSnafu { cycle })?;
+            let b_value = b.read(*port).context(PortAccessSnafu { cycle })?;
+            if !masked_eq(&a_value, &b_value, *mask) {
+                return MismatchSnafu {
+                    cycle,
+                    port: *port,
+                    a: a_value,
+                    b: b_value,
+                }
+                .fail();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The expected value of an output port in a [`TestVector`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// The port must read exactly this value.
+    Exact(VerilatorValue),
+    /// The port must read this value in the bits set in `mask`; the other bits
+    /// are don't-cares. As in [`compare_models_masked`], any bits above the
+    /// low 64 of a wider port are always compared.
+    Masked { value: VerilatorValue, mask: u64 },
+    /// The port may read any value.
+    DontCare,
+}
+
+impl Expected {
+    fn matches(&self, actual: &VerilatorValue) -> bool {
+        match self {
+            Self::Exact(value) => masked_eq(value, actual, u64::MAX),
+            Self::Masked { value, mask } => masked_eq(value, actual, *mask),
+            Self::DontCare => true,
+        }
+    }
+}
+
+impl From<VerilatorValue> for Expected {
+    fn from(value: VerilatorValue) -> Self {
+        Self::Exact(value)
+    }
+}
+
+/// The values to pin on a model for one evaluation, along with the values its
+/// outputs are expected to read afterward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector<'port> {
+    pub inputs: Vec<(&'port str, VerilatorValue)>,
+    pub outputs: Vec<(&'port str, Expected)>,
+}
+
+/// The reason [`run_vectors`] stopped.
+#[derive(Debug, Snafu)]
+pub enum VectorFailure {
+    #[snafu(display(
+        "Test vector {vector} failed: port {port} was {actual} but expected {expected:?}"
+    ))]
+    Unexpected {
+        vector: usize,
+        port: String,
+        expected: Expected,
+        actual: VerilatorValue,
+    },
+    #[snafu(display("Failed to access a port in test vector {vector}"))]
+    VectorPortAccess {
+        vector: usize,
+        source: DynamicVerilatedModelError,
+    },
+}
+
+/// For each of `vectors` in order, pins its inputs on `model`, evaluates the
+/// model, and checks its outputs. Returns the first output that does not
+/// match.
+pub fn run_vectors<'ctx, 'port>(
+    model: &mut impl AsDynamicVerilatedModel<'ctx>,
+    vectors: impl IntoIterator<Item = TestVector<'port>>,
+) -> Result<(), VectorFailure> {
+    for (vector, TestVector { inputs, outputs }) in
+        vectors.into_iter().enumerate()
+    {
+        for (port, value) in inputs {
+            model
+                .pin(port, value)
+                .context(VectorPortAccessSnafu { vector })?;
+        }
+        model.eval();
+
+        for (port, expected) in outputs {
+            let actual =
+                model.read(port).context(VectorPortAccessSnafu { vector })?;
+            if !expected.matches(&actual) {
+                return UnexpectedSnafu {
+                    vector,
+                    port,
+                    expected,
+                    actual,
+                }
+                .fail();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `a` and `b` agree in the bits set in `mask` and in every bit above
+/// the low 64, regardless of how wide a type either is stored as.
+fn masked_eq(a: &VerilatorValue, b: &VerilatorValue, mask: u64) -> bool {
+    let (a, b) = (a.to_words(), b.to_words());
+    let mask = [mask as u32, (mask >> 32) as u32];
+    (0..a.len().max(b.len())).all(|i| {
+        let word_mask = mask.get(i).copied().unwrap_or(u32::MAX);
+        let a_word = a.get(i).copied().unwrap_or(0);
+        let b_word = b.get(i).copied().unwrap_or(0);
+        a_word & word_mask == b_word & word_mask
+    })
+}
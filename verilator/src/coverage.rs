@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Verilator coverage collection. Models built with some
+//! [`crate::VerilatedModelConfig::coverage`] enabled can write the coverage
+//! counted so far with `write_coverage`, in the `coverage.dat` format read by
+//! `verilator_coverage`.
+
+use std::{ffi, path::Path};
+
+use libloading::Library;
+use snafu::{ResultExt, Whatever};
+
+/// The kinds of coverage a model collects. Each enabled kind adds
+/// instrumentation that slows down evaluation, so enable only the ones you
+/// need.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoverageKinds {
+    /// Whether to count how often each line or block executes. This passes
+    /// the `--coverage-line` flag.
+    pub line: bool,
+
+    /// Whether to count how often each signal toggles, which can also be
+    /// written out as a SAIF file for power estimation with `write_saif` on
+    /// the model. This passes the `--coverage-toggle` flag. See
+    /// [`crate::saif`] for details.
+    pub toggle: bool,
+
+    /// Whether to count user-inserted `cover property` statements. This passes
+    /// the `--coverage-user` flag.
+    pub user: bool,
+}
+
+impl CoverageKinds {
+    /// Whether any kind of coverage is enabled.
+    pub fn any(&self) -> bool {
+        self.line || self.toggle || self.user
+    }
+
+    pub(crate) fn verilator_flags(&self) -> impl Iterator<Item = &'static str> {
+        [
+            (self.line, "--coverage-line"),
+            (self.toggle, "--coverage-toggle"),
+            (self.user, "--coverage-user"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, flag)| enabled.then_some(flag))
+    }
+}
+
+#[doc(hidden)]
+pub fn write_coverage_from_library(
+    library: &Library,
+    top_module: &str,
+    model: *mut ffi::c_void,
+    path: &Path,
+) -> Result<(), Whatever> {
+    let write_coverage: libloading::Symbol<
+        extern "C" fn(*mut ffi::c_void, *const ffi::c_char),
+    > = unsafe {
+        library.get(format!("ffi_V{top_module}_write_coverage").as_bytes())
+    }
+    .whatever_context(
+        "Coverage was not collected for this model: enable some `coverage` in its configuration",
+    )?;
+
+    let c_path = ffi::CString::new(path.as_os_str().as_encoded_bytes())
+        .whatever_context("Failed to convert coverage path to C string")?;
+    (*write_coverage)(model, c_path.as_ptr());
+    Ok(())
+}
@@ -26,4 +26,11 @@ pub trait DpiFunction: Sync {
 
     /// The Rust function as a function pointer.
     fn pointer(&self) -> *const ffi::c_void;
+
+    /// A C preprocessor condition, such as `defined(__linux__)`, under which
+    /// the generated C function is compiled. If `None`, the default, the C
+    /// function is always compiled.
+    fn c_guard(&self) -> Option<&'static str> {
+        None
+    }
 }
@@ -0,0 +1,90 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A handle to a dynamic model in the style of
+//! [cocotb](https://www.cocotb.org), to ease porting existing testbenches:
+//!
+//! | cocotb                        | Marlin                              |
+//! | ----------------------------- | ----------------------------------- |
+//! | `dut.data.value = 5`          | `dut.set("data", 5u8)?`             |
+//! | `dut.out.value`               | `dut.get("out")?`                   |
+//! | `await RisingEdge(dut.clk)`   | `dut.rising_edge("clk")?`           |
+//!
+//! ```no_run
+//! # use marlin_verilator::*;
+//! # use marlin_verilator::dut::*;
+//! # use snafu::ResultExt;
+//! # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+//! # || -> Result<(), snafu::Whatever> {
+//! # let mut model = runtime.create_dyn_model("main", "src/main.sv", &[], VerilatedModelConfig::default())?;
+//! let mut dut = Dut::new(&mut model);
+//! dut.set("data", 5u8).whatever_context("Failed to drive data")?;
+//! dut.rising_edge("clk").whatever_context("Failed to clock")?;
+//! let out = dut.get("out").whatever_context("Failed to read out")?;
+//! # Ok(()) };
+//! ```
+//!
+//! Unlike in cocotb, time only advances through [`Dut::rising_edge`], and
+//! values set take effect on the next evaluation.
+
+use crate::{
+    AsDynamicVerilatedModel,
+    dynamic::{
+        DynamicVerilatedModel, DynamicVerilatedModelError, VerilatorValue,
+    },
+};
+
+/// Accesses the signals of a dynamic model by name. See the [module-level
+/// documentation](self).
+pub struct Dut<'model, 'ctx> {
+    model: &'model mut DynamicVerilatedModel<'ctx>,
+}
+
+impl<'model, 'ctx> Dut<'model, 'ctx> {
+    /// Creates a handle to `model`.
+    pub fn new(model: &'model mut DynamicVerilatedModel<'ctx>) -> Self {
+        Self { model }
+    }
+
+    /// Sets the input port `port` to `value`, like assigning to
+    /// `dut.port.value` in cocotb. See [`AsDynamicVerilatedModel::pin`].
+    pub fn set(
+        &mut self,
+        port: impl Into<String>,
+        value: impl Into<VerilatorValue>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        self.model.pin(port, value)
+    }
+
+    /// Gets the value of the output port `port`, like reading
+    /// `dut.port.value` in cocotb. See [`AsDynamicVerilatedModel::read`].
+    pub fn get(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<VerilatorValue, DynamicVerilatedModelError> {
+        self.model.read(port)
+    }
+
+    /// Drives the input port `clock` low and then high, evaluating the model
+    /// after each, so that the model has seen a full cycle ending in a rising
+    /// edge, like `await RisingEdge(dut.clock)` in cocotb.
+    pub fn rising_edge(
+        &mut self,
+        clock: impl Into<String>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let clock = clock.into();
+        self.model.pin(clock.as_str(), 0u8)?;
+        self.model.eval();
+        self.model.pin(clock, 1u8)?;
+        self.model.eval();
+        Ok(())
+    }
+
+    /// Returns the model this handle accesses.
+    pub fn model(&mut self) -> &mut DynamicVerilatedModel<'ctx> {
+        self.model
+    }
+}
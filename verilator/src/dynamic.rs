@@ -6,23 +6,120 @@
 
 //! Support for dynamic models.
 
-use std::{collections::HashMap, ffi, fmt};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi, fmt, iter,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use libloading::Library;
-use snafu::Snafu;
+use snafu::{ResultExt, Snafu, Whatever};
 
-use crate::{PortDirection, types};
+use crate::{
+    PortDirection, VerilatorRuntime,
+    simulation::{self, SimulationError},
+    types,
+};
 
 /// See [`types`].
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum VerilatorValue {
     CData(types::CData),
     SData(types::SData),
     IData(types::IData),
     QData(types::QData),
+    /// The words of a port wider than 64 bits, least significant first.
+    WData(Vec<types::WData>),
 }
 
 impl VerilatorValue {
+    /// Creates the smallest value that holds a `bit_width`-bit port, failing
+    /// if `value` has any bits set at or above `bit_width`. See also
+    /// [`VerilatorValue::masked`].
+    pub fn new(value: u64, bit_width: usize) -> Result<Self, ValueTooWide> {
+        if value != value & Self::mask(bit_width) {
+            return ValueTooWideSnafu { value, bit_width }.fail();
+        }
+        Ok(Self::masked(value, bit_width))
+    }
+
+    /// Creates the smallest value that holds a `bit_width`-bit port,
+    /// discarding any bits of `value` at or above `bit_width`. Values for
+    /// ports wider than 64 bits are zero-extended.
+    pub fn masked(value: u64, bit_width: usize) -> Self {
+        let value = value & Self::mask(bit_width);
+        if bit_width <= 8 {
+            Self::CData(value as types::CData)
+        } else if bit_width <= 16 {
+            Self::SData(value as types::SData)
+        } else if bit_width <= 32 {
+            Self::IData(value as types::IData)
+        } else if bit_width <= 64 {
+            Self::QData(value)
+        } else {
+            let mut words = vec![0; bit_width.div_ceil(32)];
+            words[0] = value as types::WData;
+            words[1] = (value >> 32) as types::WData;
+            Self::WData(words)
+        }
+    }
+
+    /// Creates a value for a `bit_width`-bit port wider than 64 bits from its
+    /// words, least significant first, discarding any bits at or above
+    /// `bit_width`. Missing words are zero and extra words are dropped.
+    pub fn masked_words(
+        words: impl IntoIterator<Item = types::WData>,
+        bit_width: usize,
+    ) -> Self {
+        let word_count = bit_width.div_ceil(32);
+        let mut words = words
+            .into_iter()
+            .chain(iter::repeat(0))
+            .take(word_count)
+            .collect::<Vec<_>>();
+        if let Some(last) = words.last_mut() {
+            *last &= types::WData::MAX >> (word_count * 32 - bit_width) as u32;
+        }
+        Self::WData(words)
+    }
+
+    fn mask(bit_width: usize) -> u64 {
+        u64::MAX
+            .checked_shr(64u32.saturating_sub(bit_width as u32))
+            .unwrap_or(0)
+    }
+
+    /// The value truncated or zero-extended to 64 bits.
+    pub(crate) fn to_u64(&self) -> u64 {
+        match self {
+            Self::CData(cdata) => *cdata as u64,
+            Self::SData(sdata) => *sdata as u64,
+            Self::IData(idata) => *idata as u64,
+            Self::QData(qdata) => *qdata,
+            Self::WData(words) => {
+                let low = words.first().copied().unwrap_or(0) as u64;
+                let high = words.get(1).copied().unwrap_or(0) as u64;
+                high << 32 | low
+            }
+        }
+    }
+
+    /// The words of the value, least significant first, as a port wider than
+    /// 64 bits would store it.
+    pub(crate) fn to_words(&self) -> Vec<types::WData> {
+        match self {
+            Self::WData(words) => words.clone(),
+            _ => {
+                let value = self.to_u64();
+                vec![value as types::WData, (value >> 32) as types::WData]
+            }
+        }
+    }
+
     /// The maximum number of bits this value takes up.
     pub fn width(&self) -> usize {
         match self {
@@ -30,10 +127,13 @@ impl VerilatorValue {
             Self::SData(_) => 16,
             Self::IData(_) => 32,
             Self::QData(_) => 64,
+            Self::WData(words) => words.len() * 32,
         }
     }
 }
 
+/// Values up to 64 bits wide are displayed in decimal, and wider values in
+/// hexadecimal, e.g., `0x1_00000000_00000000`.
 impl fmt::Display for VerilatorValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -41,10 +141,41 @@ impl fmt::Display for VerilatorValue {
             VerilatorValue::SData(sdata) => sdata.fmt(f),
             VerilatorValue::IData(idata) => idata.fmt(f),
             VerilatorValue::QData(qdata) => qdata.fmt(f),
+            VerilatorValue::WData(words) => {
+                let mut words =
+                    words.iter().rev().skip_while(|word| **word == 0);
+                write!(f, "{:#x}", words.next().copied().unwrap_or(0))?;
+                for word in words {
+                    write!(f, "_{word:08x}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+/// Serializes as the numeric value along with [`VerilatorValue::width`], e.g.,
+/// `{"value":5,"width":8}` in JSON. Values wider than 64 bits serialize their
+/// value as the hexadecimal string they display as.
+#[cfg(feature = "serde")]
+impl serde::Serialize for VerilatorValue {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("VerilatorValue", 2)?;
+        if let Self::WData(_) = self {
+            state.serialize_field("value", &self.to_string())?;
+        } else {
+            state.serialize_field("value", &self.to_u64())?;
+        }
+        state.serialize_field("width", &self.width())?;
+        state.end()
+    }
+}
+
 impl From<types::CData> for VerilatorValue {
     fn from(value: types::CData) -> Self {
         Self::CData(value)
@@ -68,10 +199,29 @@ impl From<types::QData> for VerilatorValue {
     }
 }
 
+impl From<Vec<types::WData>> for VerilatorValue {
+    fn from(words: Vec<types::WData>) -> Self {
+        Self::WData(words)
+    }
+}
+
+/// Error returned by [`VerilatorValue::new`] when a value does not fit in the
+/// requested bit width.
+#[derive(Debug, Snafu)]
+#[snafu(display("Value {value:#x} does not fit in {bit_width} bits"))]
+pub struct ValueTooWide {
+    pub value: u64,
+    pub bit_width: usize,
+}
+
 /// Access model ports at runtime.
 pub trait AsDynamicVerilatedModel<'ctx>: 'ctx {
+    /// Equivalent to the Verilator `eval` method.
+    fn eval(&mut self);
+
     /// If `port` is a valid port name for this model, returns the current value
-    /// of the port.
+    /// of the port. For [`DynamicVerilatedModel`], any bits at or above the
+    /// port's declared width are zero.
     fn read(
         &self,
         port: impl Into<String>,
@@ -86,21 +236,585 @@ pub trait AsDynamicVerilatedModel<'ctx>: 'ctx {
     ) -> Result<(), DynamicVerilatedModelError>;
 }
 
+type EvalCallback<'ctx> = Box<dyn FnMut(&DynamicVerilatedModel<'ctx>) + 'ctx>;
+
 /// A hardware model constructed at runtime. See
 /// [`super::VerilatorRuntime::create_dyn_model`].
 pub struct DynamicVerilatedModel<'ctx> {
     // TODO: add the dlsyms here and remove the library field
-    pub(crate) ports: HashMap<String, (usize, PortDirection)>,
+    /// The width and direction of each port, and whether it is signed (see
+    /// [`DynamicVerilatedModel::set_signed_port`]).
+    pub(crate) ports: HashMap<String, (usize, PortDirection, bool)>,
     pub(crate) name: String,
     pub(crate) main: *mut ffi::c_void,
     pub(crate) eval_main: extern "C" fn(*mut ffi::c_void),
+    pub(crate) final_main: extern "C" fn(*mut ffi::c_void),
+    pub(crate) finish_code_main: extern "C" fn(*mut ffi::c_void) -> ffi::c_int,
+    pub(crate) set_quiet_main: extern "C" fn(bool),
     pub(crate) library: &'ctx Library,
+    pub(crate) runtime: &'ctx VerilatorRuntime,
+    pub(crate) clock_port: Option<String>,
+    pub(crate) reset_port: Option<String>,
+    /// The input ports pinned since construction or the last reset.
+    pub(crate) driven_inputs: HashSet<String>,
+    /// See [`DynamicVerilatedModel::set_post_reset_inputs`].
+    pub(crate) post_reset_inputs: Vec<(String, VerilatorValue)>,
+    /// See [`DynamicVerilatedModel::cancellation_flag`].
+    pub(crate) cancelled: Arc<AtomicBool>,
+    /// See [`DynamicVerilatedModel::on_eval`].
+    pub(crate) on_eval: Option<EvalCallback<'ctx>>,
 }
 
-impl DynamicVerilatedModel<'_> {
+impl<'ctx> DynamicVerilatedModel<'ctx> {
     /// Equivalent to the Verilator `eval` method.
     pub fn eval(&mut self) {
         (self.eval_main)(self.main);
+        if let Some(mut on_eval) = self.on_eval.take() {
+            on_eval(self);
+            self.on_eval = Some(on_eval);
+        }
+    }
+
+    /// Calls `callback` with the model at the end of every
+    /// [`DynamicVerilatedModel::eval`], including those made by methods like
+    /// [`DynamicVerilatedModel::tick`], e.g., to log a signal or check an
+    /// invariant every cycle. This replaces any previously registered
+    /// callback.
+    pub fn on_eval(&mut self, callback: impl FnMut(&Self) + 'ctx) {
+        self.on_eval = Some(Box::new(callback));
+    }
+
+    /// Equivalent to the Verilator `final` method, which runs the `final`
+    /// blocks in the Verilog source code. Call this once at the end of
+    /// simulation: the `final` blocks run at most once, so calling this again,
+    /// or [`super::VerilatorRuntime::finalize`] afterward, does nothing.
+    pub fn finalize(&mut self) {
+        if self.runtime.mark_finalized(self.main) {
+            (self.final_main)(self.main);
+        }
+    }
+
+    /// Ends the simulation of this model: like
+    /// [`DynamicVerilatedModel::finalize`], runs the `final` blocks in the
+    /// Verilog source code, but then reports whether the simulation ended with
+    /// an error, e.g., from a failed assertion or `$stop`, and frees the model.
+    /// Errors raised in the `final` blocks are reported instead of aborting
+    /// the process. A `$finish` is a normal end to a simulation.
+    pub fn finish(self) -> Result<(), SimulationError> {
+        simulation::finish_model(self.library, &self.name, self.main)
+    }
+
+    /// If the design has ended the simulation during an evaluation, returns
+    /// the conventional exit status: `Some(0)` after a `$finish`, or `Some(1)`
+    /// if it also ended with an error (see [`DynamicVerilatedModel::finish`]).
+    /// Otherwise, returns `None`.
+    ///
+    /// Verilator does not record the argument of `$finish`, which only sets
+    /// how much diagnostic information to print, so it does not affect the
+    /// status.
+    pub fn finish_code(&self) -> Option<i32> {
+        simulation::finish_code(self.finish_code_main, self.main)
+    }
+
+    /// Uses `port` as the clock for [`DynamicVerilatedModel::tick`]. The port
+    /// must have been specified as an input when creating this model.
+    pub fn set_clock_port(
+        &mut self,
+        port: impl Into<String>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let port = port.into();
+        self.check_input_port(&port)?;
+        self.clock_port = Some(port);
+        Ok(())
+    }
+
+    /// Uses `port` as the active-high reset for
+    /// [`DynamicVerilatedModel::reset`]. The port must have been specified as
+    /// an input when creating this model.
+    pub fn set_reset_port(
+        &mut self,
+        port: impl Into<String>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let port = port.into();
+        self.check_input_port(&port)?;
+        self.reset_port = Some(port);
+        Ok(())
+    }
+
+    /// Marks `port` as declared `signed` in the Verilog source, so that
+    /// [`DynamicVerilatedModel::read_signed`] can read it. Verilator does not
+    /// record signedness in its interface, so every port of a dynamic model
+    /// starts out unsigned.
+    pub fn set_signed_port(
+        &mut self,
+        port: impl Into<String>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let port = port.into();
+        let Some((_, _, is_signed)) = self.ports.get_mut(&port) else {
+            return Err(DynamicVerilatedModelError::NoSuchPort {
+                top_module: self.name.clone(),
+                port,
+                source: None,
+            });
+        };
+        *is_signed = true;
+        Ok(())
+    }
+
+    /// Drives the clock low and then high, evaluating the model after each
+    /// edge. See [`DynamicVerilatedModel::set_clock_port`].
+    pub fn tick(&mut self) -> Result<(), DynamicVerilatedModelError> {
+        let Some(clock_port) = self.clock_port.clone() else {
+            return Err(DynamicVerilatedModelError::NoClockPort {
+                top_module: self.name.clone(),
+            });
+        };
+        self.pin(clock_port.as_str(), VerilatorValue::CData(0))?;
+        self.eval();
+        self.pin(clock_port, VerilatorValue::CData(1))?;
+        self.eval();
+        Ok(())
+    }
+
+    /// Drives the input `port` high for `cycles` calls to
+    /// [`DynamicVerilatedModel::tick`], then drives it low again and evaluates
+    /// the model, e.g., to strobe a `valid` or `start` signal. This requires a
+    /// clock port (see [`DynamicVerilatedModel::set_clock_port`]).
+    pub fn pulse(
+        &mut self,
+        port: impl Into<String>,
+        cycles: usize,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let port = port.into();
+        if self.clock_port.is_none() {
+            return Err(DynamicVerilatedModelError::NoClockPort {
+                top_module: self.name.clone(),
+            });
+        }
+        self.check_input_port(&port)?;
+        let (width, _, _) = self.ports[&port];
+
+        self.pin(port.as_str(), VerilatorValue::masked(1, width))?;
+        for _ in 0..cycles {
+            self.tick()?;
+        }
+        self.pin(port, VerilatorValue::masked(0, width))?;
+        self.eval();
+        Ok(())
+    }
+
+    /// A flag that, once set (e.g., by a watchdog thread), makes
+    /// [`DynamicVerilatedModel::run_cycles`] and
+    /// [`DynamicVerilatedModel::run_until`] stop with
+    /// [`DynamicVerilatedModelError::Cancelled`]. The flag is only checked
+    /// between cycles, so it cannot interrupt a single evaluation that never
+    /// returns. It stays set until cleared.
+    pub fn cancellation_flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    /// Calls [`DynamicVerilatedModel::tick`] `cycles` times, stopping early if
+    /// the [`DynamicVerilatedModel::cancellation_flag`] is set.
+    pub fn run_cycles(
+        &mut self,
+        cycles: u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        for cycle in 0..cycles {
+            self.check_cancelled(cycle)?;
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    /// Calls [`DynamicVerilatedModel::tick`] until `done` returns `true` for
+    /// the model, returning the number of cycles run. The condition is checked
+    /// before each cycle, and the loop stops early if the
+    /// [`DynamicVerilatedModel::cancellation_flag`] is set.
+    pub fn run_until(
+        &mut self,
+        mut done: impl FnMut(&Self) -> Result<bool, DynamicVerilatedModelError>,
+    ) -> Result<u64, DynamicVerilatedModelError> {
+        let mut cycles = 0;
+        while !done(self)? {
+            self.check_cancelled(cycles)?;
+            self.tick()?;
+            cycles += 1;
+        }
+        Ok(cycles)
+    }
+
+    fn check_cancelled(
+        &self,
+        cycles: u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(DynamicVerilatedModelError::Cancelled {
+                top_module: self.name.clone(),
+                cycles,
+            });
+        }
+        Ok(())
+    }
+
+    /// Asserts the reset for one [`DynamicVerilatedModel::tick`] and then
+    /// deasserts it, pinning any inputs set with
+    /// [`DynamicVerilatedModel::set_post_reset_inputs`]. See
+    /// [`DynamicVerilatedModel::set_reset_port`].
+    ///
+    /// This also restarts the tracking for
+    /// [`DynamicVerilatedModel::undriven_inputs`], after which only the clock,
+    /// reset, and post-reset input ports count as driven.
+    pub fn reset(&mut self) -> Result<(), DynamicVerilatedModelError> {
+        let Some(reset_port) = self.reset_port.clone() else {
+            return Err(DynamicVerilatedModelError::NoResetPort {
+                top_module: self.name.clone(),
+            });
+        };
+        self.driven_inputs.clear();
+        self.pin(reset_port.as_str(), VerilatorValue::CData(1))?;
+        self.tick()?;
+        self.pin(reset_port, VerilatorValue::CData(0))?;
+        for (port, value) in self.post_reset_inputs.clone() {
+            self.pin(port, value)?;
+        }
+        Ok(())
+    }
+
+    /// Like [`DynamicVerilatedModel::reset`], but then evaluates the model
+    /// `settle_evals` times so that combinational paths depending on the
+    /// deasserted reset and the post-reset inputs have settled before the
+    /// outputs are read.
+    pub fn reset_and_settle(
+        &mut self,
+        settle_evals: usize,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        self.reset()?;
+        for _ in 0..settle_evals {
+            self.eval();
+        }
+        Ok(())
+    }
+
+    /// Sets the values [`DynamicVerilatedModel::reset`] pins on the given
+    /// input ports right after deasserting the reset, so each test starts from
+    /// a known input state. Like any pinned value, they take effect on the
+    /// next evaluation. This replaces any previously set values.
+    pub fn set_post_reset_inputs<'port>(
+        &mut self,
+        inputs: impl IntoIterator<Item = (&'port str, VerilatorValue)>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let mut post_reset_inputs = vec![];
+        for (port, value) in inputs {
+            self.check_input_port(port)?;
+            post_reset_inputs.push((port.to_string(), value));
+        }
+        self.post_reset_inputs = post_reset_inputs;
+        Ok(())
+    }
+
+    /// Evaluates the model and returns a snapshot of every output (and inout)
+    /// port by name, e.g., to compare against expected values or store per
+    /// cycle. With the `serde` feature, the snapshot can be serialized.
+    pub fn read_outputs(
+        &mut self,
+    ) -> Result<BTreeMap<String, VerilatorValue>, DynamicVerilatedModelError>
+    {
+        self.eval();
+        let outputs = self
+            .ports
+            .iter()
+            .filter(|(_, (_, direction, _))| {
+                matches!(
+                    direction,
+                    PortDirection::Output | PortDirection::Inout
+                )
+            })
+            .map(|(port, _)| port.clone())
+            .collect::<Vec<_>>();
+        outputs
+            .into_iter()
+            .map(|port| {
+                let value = self.read(port.as_str())?;
+                Ok((port, value))
+            })
+            .collect()
+    }
+
+    /// Ticks the clock `cycles` times like [`DynamicVerilatedModel::tick`],
+    /// returning a snapshot of every output port after each cycle as with
+    /// [`DynamicVerilatedModel::read_outputs`], e.g., to compare against an
+    /// expected output sequence.
+    pub fn record_outputs(
+        &mut self,
+        cycles: usize,
+    ) -> Result<Vec<BTreeMap<String, VerilatorValue>>, DynamicVerilatedModelError>
+    {
+        (0..cycles)
+            .map(|_| {
+                self.tick()?;
+                self.read_outputs()
+            })
+            .collect()
+    }
+
+    /// The input ports that have not been pinned since this model was created
+    /// or last [`DynamicVerilatedModel::reset`], in sorted order. Verilator
+    /// holds such ports at zero, which is usually a testbench mistake.
+    pub fn undriven_inputs(&self) -> Vec<&str> {
+        let mut undriven_inputs = self
+            .ports
+            .iter()
+            .filter(|(port, (_, direction, _))| {
+                matches!(direction, PortDirection::Input | PortDirection::Inout)
+                    && !self.driven_inputs.contains(*port)
+            })
+            .map(|(port, _)| port.as_str())
+            .collect::<Vec<_>>();
+        undriven_inputs.sort_unstable();
+        undriven_inputs
+    }
+
+    /// Pins every input port except the clock and reset ports to a random
+    /// value masked to the port's width. Each value is drawn by calling
+    /// `next_u64` once per port (or per 64 bits of a port wider than 64 bits)
+    /// in sorted port order, so a seeded random
+    /// number generator, e.g., `|| rng.next_u64()`, gives repeatable inputs
+    /// without this crate depending on any particular one.
+    pub fn randomize_inputs(
+        &mut self,
+        mut next_u64: impl FnMut() -> u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let mut inputs = self
+            .ports
+            .iter()
+            .filter(|(port, (_, direction, _))| {
+                matches!(direction, PortDirection::Input | PortDirection::Inout)
+                    && self.clock_port.as_ref() != Some(*port)
+                    && self.reset_port.as_ref() != Some(*port)
+            })
+            .map(|(port, (width, _, _))| (port.clone(), *width))
+            .collect::<Vec<_>>();
+        inputs.sort_unstable();
+
+        for (port, width) in inputs {
+            let value = if width <= 64 {
+                VerilatorValue::masked(next_u64(), width)
+            } else {
+                let words = (0..width.div_ceil(64))
+                    .map(|_| next_u64())
+                    .flat_map(|value| {
+                        [value as types::WData, (value >> 32) as types::WData]
+                    });
+                VerilatorValue::masked_words(words, width)
+            };
+            self.pin(port, value)?;
+        }
+        Ok(())
+    }
+
+    fn check_input_port(
+        &self,
+        port: &str,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let (_, direction, _) = *self.ports.get(port).ok_or_else(|| {
+            DynamicVerilatedModelError::NoSuchPort {
+                top_module: self.name.clone(),
+                port: port.to_string(),
+                source: None,
+            }
+        })?;
+        if !matches!(direction, PortDirection::Input | PortDirection::Inout) {
+            return Err(DynamicVerilatedModelError::InvalidPortDirection {
+                top_module: self.name.clone(),
+                port: port.to_string(),
+                direction,
+                attempted_direction: PortDirection::Input,
+            });
+        }
+        Ok(())
+    }
+
+    /// Suppresses (if `quiet`) or restores output printed by the model, such
+    /// as from `$display`. This affects every model sharing the same library,
+    /// i.e., created from the same module, ports, and configuration. See also
+    /// [`crate::VerilatedModelConfig::quiet`].
+    pub fn set_quiet(&mut self, quiet: bool) {
+        (self.set_quiet_main)(quiet);
+    }
+
+    /// Replaces the command-line arguments the model sees with `args`, e.g.,
+    /// `&["+SEED=42"]`, so that the design can read them with
+    /// `$value$plusargs` and `$test$plusargs`. Call this before the first
+    /// evaluation so that `initial` blocks observe the arguments. Each of
+    /// `args` is passed as its own argument, even if it contains spaces. The
+    /// arguments belong to the model's Verilator context, so they are shared
+    /// by every model sharing the same library or, with
+    /// [`crate::VerilatorRuntimeOptions::shared_context`], the same runtime.
+    /// Fails if any of `args` contains a null byte.
+    pub fn set_plusargs(&mut self, args: &[&str]) -> Result<(), Whatever> {
+        let command_args: libloading::Symbol<
+            extern "C" fn(
+                *mut ffi::c_void,
+                ffi::c_int,
+                *const *const ffi::c_char,
+            ),
+        > = unsafe {
+            self.library
+                .get(format!("ffi_V{}_commandArgs", self.name).as_bytes())
+        }
+        .whatever_context(format!(
+            "Failed to load argument setter for module {}",
+            self.name
+        ))?;
+        let c_args = iter::once("marlin")
+            .chain(args.iter().copied())
+            .map(|arg| {
+                ffi::CString::new(arg).whatever_context(format!(
+                    "Plusarg {arg:?} cannot contain null bytes"
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let argv = c_args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+        (*command_args)(self.main, argv.len() as ffi::c_int, argv.as_ptr());
+        Ok(())
+    }
+
+    /// Writes the coverage counted so far to `path` in the format read by
+    /// `verilator_coverage`. The model must have been built with some
+    /// [`crate::VerilatedModelConfig::coverage`] enabled. The coverage belongs
+    /// to the model's Verilator context, so it is shared by every model
+    /// sharing the same library or, with
+    /// [`crate::VerilatorRuntimeOptions::shared_context`], the same runtime.
+    pub fn write_coverage(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), Whatever> {
+        crate::coverage::write_coverage_from_library(
+            self.library,
+            &self.name,
+            self.main,
+            path.as_ref(),
+        )
+    }
+
+    /// Writes the toggle activity counted so far to a SAIF file at `path`,
+    /// recording `duration_ps` as the length of the simulation in
+    /// picoseconds. The model must have been built with
+    /// [`crate::coverage::CoverageKinds::toggle`] coverage. Like the coverage
+    /// written by [`DynamicVerilatedModel::write_coverage`], the activity is
+    /// shared by every model sharing the same Verilator context.
+    pub fn write_saif(
+        &self,
+        path: impl AsRef<Path>,
+        duration_ps: u64,
+    ) -> Result<(), Whatever> {
+        crate::saif::write_saif_from_library(
+            self.library,
+            &self.name,
+            self.main,
+            path.as_ref(),
+            duration_ps,
+        )
+    }
+
+    /// Pins `port` like [`AsDynamicVerilatedModel::pin`], but first discards
+    /// any bits of `value` at or above the port's width, so `value` may be of
+    /// any type, e.g., a `u64` driving an 8-bit port. Use this only where
+    /// truncation is intended; otherwise, [`AsDynamicVerilatedModel::pin`]
+    /// catches values of the wrong type.
+    pub fn pin_truncating(
+        &mut self,
+        port: impl Into<String>,
+        value: impl Into<VerilatorValue>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        let port: String = port.into();
+        let (width, _, _) = *self.ports.get(&port).ok_or_else(|| {
+            DynamicVerilatedModelError::NoSuchPort {
+                top_module: self.name.clone(),
+                port: port.clone(),
+                source: None,
+            }
+        })?;
+        let value = value.into();
+        let value = if width <= 64 {
+            VerilatorValue::masked(value.to_u64(), width)
+        } else {
+            VerilatorValue::masked_words(value.to_words(), width)
+        };
+        self.pin(port, value)
+    }
+
+    /// Reads `port` like [`AsDynamicVerilatedModel::read`], returning its value
+    /// as `ceil(width / 8)` little-endian bytes regardless of which type the
+    /// port is stored as. Bits of the last byte above the port's width are
+    /// always zero.
+    pub fn read_bytes(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<Vec<u8>, DynamicVerilatedModelError> {
+        let port: String = port.into();
+        let mut bytes = match self.read(port.clone())? {
+            VerilatorValue::CData(cdata) => cdata.to_le_bytes().to_vec(),
+            VerilatorValue::SData(sdata) => sdata.to_le_bytes().to_vec(),
+            VerilatorValue::IData(idata) => idata.to_le_bytes().to_vec(),
+            VerilatorValue::QData(qdata) => qdata.to_le_bytes().to_vec(),
+            VerilatorValue::WData(words) => {
+                words.iter().flat_map(|word| word.to_le_bytes()).collect()
+            }
+        };
+        let (width, _, _) = self.ports[&port];
+        bytes.truncate(width.div_ceil(8));
+        if width % 8 != 0 {
+            if let Some(last) = bytes.last_mut() {
+                *last &= (1 << (width % 8)) - 1;
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Reads `port` like [`AsDynamicVerilatedModel::read`], but interprets the
+    /// value as a two's complement integer of the port's width, e.g., so that
+    /// an `output signed [11:0] sample` holding `12'hfff` reads as `-1`.
+    ///
+    /// The port must have been marked with
+    /// [`DynamicVerilatedModel::set_signed_port`]. Ports wider than 64 bits
+    /// cannot be read this way.
+    pub fn read_signed(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<i64, DynamicVerilatedModelError> {
+        let port: String = port.into();
+        if let Some((_, _, false)) = self.ports.get(&port) {
+            return Err(DynamicVerilatedModelError::UnsignedPort {
+                top_module: self.name.clone(),
+                port,
+            });
+        }
+        let value = match self.read(port.clone())? {
+            VerilatorValue::CData(cdata) => cdata as u64,
+            VerilatorValue::SData(sdata) => sdata as u64,
+            VerilatorValue::IData(idata) => idata as u64,
+            VerilatorValue::QData(qdata) => qdata,
+            VerilatorValue::WData(words) => {
+                return Err(DynamicVerilatedModelError::InvalidPortWidth {
+                    top_module: self.name.clone(),
+                    port,
+                    width: words.len() * 32,
+                    attempted_lower: 1,
+                    attempted_higher: 64,
+                });
+            }
+        };
+        let (width, _, _) = self.ports[&port];
+        let unused_bits = 64 - width as u32;
+        Ok(((value << unused_bits) as i64) >> unused_bits)
+    }
+}
+
+impl Drop for DynamicVerilatedModel<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the model is not used after this
+        unsafe { self.runtime.release_model(self.main) };
     }
 }
 
@@ -135,15 +849,34 @@ pub enum DynamicVerilatedModelError {
         direction: PortDirection,
         attempted_direction: PortDirection,
     },
+    #[snafu(display(
+        "Verilated module {top_module} has no clock port: did you forget to call `set_clock_port`?"
+    ))]
+    NoClockPort { top_module: String },
+    #[snafu(display(
+        "Verilated module {top_module} has no reset port: did you forget to call `set_reset_port`?"
+    ))]
+    NoResetPort { top_module: String },
+    #[snafu(display(
+        "Port {port} on verilated module {top_module} was read as signed, but is not marked signed: did you forget to call `set_signed_port`?"
+    ))]
+    UnsignedPort { top_module: String, port: String },
+    #[snafu(display(
+        "Simulation of verilated module {top_module} was cancelled after {cycles} cycles"
+    ))]
+    Cancelled { top_module: String, cycles: u64 },
 }
 
 impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
+    fn eval(&mut self) {
+        DynamicVerilatedModel::eval(self);
+    }
     fn read(
         &self,
         port: impl Into<String>,
     ) -> Result<VerilatorValue, DynamicVerilatedModelError> {
         let port: String = port.into();
-        let (width, direction) = *self.ports.get(&port).ok_or(
+        let (width, direction, _) = *self.ports.get(&port).ok_or(
             DynamicVerilatedModelError::NoSuchPort {
                 top_module: self.name.clone(),
                 port: port.clone(),
@@ -177,7 +910,9 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
                     }
                 })?;
 
-                Ok((*symbol)($self.main).into())
+                // the high bits beyond the port's width are not guaranteed to
+                // be zero, e.g., after pinning an out-of-range input
+                Ok(VerilatorValue::masked((*symbol)($self.main) as u64, width))
             }};
         }
 
@@ -190,7 +925,23 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
         } else if width <= 64 {
             read_value!(self, port, types::QData)
         } else {
-            unreachable!("Should have been caught in create_dyn_model")
+            let symbol: libloading::Symbol<
+                extern "C" fn(*mut ffi::c_void, *mut types::WData),
+            > = unsafe {
+                self.library
+                    .get(format!("ffi_V{}_read_{}", self.name, port).as_bytes())
+            }
+            .map_err(|source| {
+                DynamicVerilatedModelError::NoSuchPort {
+                    top_module: self.name.to_string(),
+                    port: port.clone(),
+                    source: Some(source),
+                }
+            })?;
+
+            let mut words = vec![0; width.div_ceil(32)];
+            (*symbol)(self.main, words.as_mut_ptr());
+            Ok(VerilatorValue::masked_words(words, width))
         }
     }
 
@@ -200,7 +951,7 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
         value: impl Into<VerilatorValue>,
     ) -> Result<(), DynamicVerilatedModelError> {
         macro_rules! pin_value {
-            ($self:ident, $port:expr, $value:expr, $value_type:ty, $low:literal, $high:literal) => {{
+            ($self:ident, $port:expr, $value:expr, $value_type:ty, $low:expr, $high:expr) => {{
                 let symbol: libloading::Symbol<
                     extern "C" fn(*mut ffi::c_void, $value_type),
                 > = unsafe {
@@ -216,7 +967,7 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
                     }
                 })?;
 
-                let (width, direction) = $self
+                let (width, direction, _) = $self
                     .ports
                     .get(&$port)
                     .ok_or(DynamicVerilatedModelError::NoSuchPort {
@@ -251,6 +1002,7 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
                 }
 
                 (*symbol)($self.main, $value);
+                $self.driven_inputs.insert($port);
                 Ok(())
             }};
         }
@@ -269,6 +1021,33 @@ impl<'ctx> AsDynamicVerilatedModel<'ctx> for DynamicVerilatedModel<'ctx> {
             VerilatorValue::QData(qdata) => {
                 pin_value!(self, port, qdata, types::QData, 33, 64)
             }
+            VerilatorValue::WData(words) => {
+                // the FFI copies exactly as many words as the port stores
+                let word_count = words.len();
+                let (low, high) =
+                    (word_count.saturating_sub(1) * 32 + 1, word_count * 32);
+                if let Some((width, _, _)) = self.ports.get(&port) {
+                    if *width <= 64 || width.div_ceil(32) != word_count {
+                        return Err(
+                            DynamicVerilatedModelError::InvalidPortWidth {
+                                top_module: self.name.clone(),
+                                port,
+                                width: *width,
+                                attempted_lower: low,
+                                attempted_higher: high,
+                            },
+                        );
+                    }
+                }
+                pin_value!(
+                    self,
+                    port,
+                    words.as_ptr(),
+                    *const types::WData,
+                    low,
+                    high
+                )
+            }
         }
     }
 }
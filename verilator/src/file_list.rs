@@ -0,0 +1,75 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Minimal parsing of Verilator file lists (`-f` arguments), used only to
+//! discover which source files a file list provides. The file lists
+//! themselves are forwarded to Verilator as-is.
+
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use snafu::{ResultExt, Whatever};
+
+/// Returns the source files named in the file list at `path`, including those
+/// in nested file lists. Like Verilator, relative paths are resolved against
+/// the current directory, except in file lists included with `-F`, where they
+/// are resolved against the directory containing the file list.
+pub(crate) fn read_file_list(
+    path: &Utf8Path,
+) -> Result<Vec<Utf8PathBuf>, Whatever> {
+    let mut source_files = vec![];
+    read_file_list_into(path, None, &mut source_files)?;
+    Ok(source_files)
+}
+
+fn read_file_list_into(
+    path: &Utf8Path,
+    relative_to: Option<&Utf8Path>,
+    source_files: &mut Vec<Utf8PathBuf>,
+) -> Result<(), Whatever> {
+    let contents = fs::read_to_string(path)
+        .whatever_context(format!("Failed to read file list {path}"))?;
+    let resolve = |file: &str| match relative_to {
+        Some(directory) => directory.join(file),
+        None => Utf8PathBuf::from(file),
+    };
+
+    let mut tokens = contents
+        .lines()
+        .map(|line| line.split_once("//").map_or(line, |(code, _)| code))
+        .flat_map(str::split_whitespace);
+    while let Some(token) = tokens.next() {
+        match token {
+            "-f" | "-F" => {
+                let Some(nested) = tokens.next() else {
+                    break;
+                };
+                let nested = resolve(nested);
+                let nested_relative_to = (token == "-F")
+                    .then(|| nested.parent().map(Utf8Path::to_path_buf))
+                    .flatten();
+                read_file_list_into(
+                    &nested,
+                    nested_relative_to.as_deref(),
+                    source_files,
+                )?;
+            }
+            "-v" => {
+                if let Some(library_file) = tokens.next() {
+                    source_files.push(resolve(library_file));
+                }
+            }
+            // options with a separate argument that is not a source file
+            "-y" | "-o" | "-top" | "--top-module" | "--Mdir" | "-Mdir" => {
+                tokens.next();
+            }
+            _ if token.starts_with(['-', '+']) => {}
+            _ => source_files.push(resolve(token)),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Discovery of the signals and scopes inside a built model. See
+//! [`super::VerilatorRuntime::model_hierarchy`].
+
+use std::{collections::BTreeSet, fs};
+
+use camino::Utf8Path;
+use snafu::{Whatever, whatever};
+
+/// The hierarchy of a Verilated model as reported by the headers Verilator
+/// generates. Hierarchical paths are dot-separated and start with the name of
+/// the top module, e.g., `main.counter.count`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelHierarchy {
+    /// The top-level ports of the model, in declaration order.
+    pub ports: Vec<String>,
+
+    /// Every scope (the top module and all submodule instances) that contains
+    /// at least one signal Verilator kept, in sorted order.
+    pub scopes: Vec<String>,
+
+    /// Every internal signal Verilator kept, in sorted order.
+    pub signals: Vec<String>,
+}
+
+/// Reads the hierarchy of the model for `top_module` from the Verilator
+/// output directory `obj_dir`.
+pub(crate) fn read_hierarchy(
+    obj_dir: &Utf8Path,
+    top_module: &str,
+) -> Result<ModelHierarchy, Whatever> {
+    // Verilator 5 places the design state in the root header, whereas older
+    // versions place it in the model header itself
+    let headers = [
+        obj_dir.join(format!("V{top_module}___024root.h")),
+        obj_dir.join(format!("V{top_module}.h")),
+    ];
+
+    let mut hierarchy = ModelHierarchy::default();
+    let mut scopes = BTreeSet::new();
+    let mut signals = BTreeSet::new();
+    let mut found_header = false;
+    for header in headers.iter().filter(|header| header.is_file()) {
+        found_header = true;
+        let contents = match fs::read_to_string(header) {
+            Ok(contents) => contents,
+            Err(error) => {
+                whatever!("Failed to read Verilator header {header}: {error}")
+            }
+        };
+
+        for line in contents.lines().map(str::trim) {
+            if let Some(port) = parse_port_declaration(line) {
+                if !hierarchy.ports.iter().any(|existing| existing == port) {
+                    hierarchy.ports.push(port.to_string());
+                }
+            } else if let Some(signal) = parse_signal_declaration(line) {
+                let path = signal.replace("__DOT__", ".");
+                let mut scope = path.as_str();
+                while let Some((parent, _)) = scope.rsplit_once('.') {
+                    scopes.insert(parent.to_string());
+                    scope = parent;
+                }
+                signals.insert(path);
+            }
+        }
+    }
+
+    if !found_header {
+        whatever!(
+            "No Verilator headers for module {top_module} found in {obj_dir}: has the model been created with this runtime yet?"
+        );
+    }
+
+    hierarchy.scopes = scopes.into_iter().collect();
+    hierarchy.signals = signals.into_iter().collect();
+    Ok(hierarchy)
+}
+
+/// Parses a line like `VL_IN8(clk,0,0);`, returning the port name. The model
+/// header of Verilator 5 declares its ports as references, like
+/// `VL_IN8(&clk,0,0);`, which yields the same name.
+fn parse_port_declaration(line: &str) -> Option<&str> {
+    let arguments = ["VL_IN", "VL_OUT", "VL_INOUT"]
+        .iter()
+        .find(|prefix| line.starts_with(*prefix))
+        .and_then(|_| line.split_once('('))?
+        .1;
+    arguments
+        .split(',')
+        .next()
+        .map(|port| port.trim().trim_start_matches('&').trim())
+}
+
+/// Parses a line like `CData/*0:0*/ main__DOT__sub__DOT__r;`, returning the
+/// mangled signal name. Verilator-internal signals are skipped.
+fn parse_signal_declaration(line: &str) -> Option<&str> {
+    let declaration = line.strip_suffix(';')?;
+    let name = declaration.rsplit([' ', '*', '/']).next()?;
+    if name.contains("__DOT__")
+        && !name.starts_with("__V")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        Some(name)
+    } else {
+        None
+    }
+}
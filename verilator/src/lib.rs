@@ -13,34 +13,55 @@
 //! which just wraps [`VerilatorRuntime`].
 
 use std::{
-    cell::RefCell,
-    collections::{HashMap, hash_map::Entry},
+    cell::{OnceCell, RefCell},
+    collections::{HashMap, HashSet, hash_map::Entry},
     ffi::{self, OsString},
     fmt, fs,
     hash::{self, Hash, Hasher},
     io::Write,
-    os::fd::FromRawFd,
-    sync::{LazyLock, Mutex},
+    ops::{Deref, DerefMut},
+    slice,
+    sync::{Arc, LazyLock, Mutex, atomic::AtomicBool},
     time::Instant,
 };
 
 use boxcar::Vec as BoxcarVec;
 use build_library::build_library;
 use camino::{Utf8Path, Utf8PathBuf};
+use coverage::CoverageKinds;
 use dashmap::DashMap;
 use dpi::DpiFunction;
 use dynamic::DynamicVerilatedModel;
+use hierarchy::ModelHierarchy;
 use libloading::Library;
 use owo_colors::OwoColorize;
 use snafu::{ResultExt, Whatever, whatever};
 
+pub mod bench;
 mod build_library;
+pub mod clock;
+pub mod compare;
+pub mod coverage;
 pub mod dpi;
+pub mod dut;
 pub mod dynamic;
+mod file_list;
+pub mod hierarchy;
+pub mod manifest;
+pub mod replay;
+pub mod saif;
+pub mod simulation;
 pub mod vcd;
 
 pub use dynamic::AsDynamicVerilatedModel;
 
+#[doc(hidden)]
+pub mod __reexports {
+    #[cfg(feature = "serde")]
+    pub use serde;
+    pub use snafu;
+}
+
 /// Verilator-defined types for C FFI.
 pub mod types {
     /// From the Verilator documentation: "Data representing 'bit' of 1-8 packed
@@ -87,6 +108,26 @@ impl fmt::Display for PortDirection {
     }
 }
 
+/// A port on a model along with how it was declared in the source code, e.g.,
+/// for tooling that renders ports with their Verilog syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortDescriptor {
+    pub name: &'static str,
+    pub msb: usize,
+    pub lsb: usize,
+    pub direction: PortDirection,
+    /// The packed dimensions exactly as declared, e.g., `[WIDTH-1:0]`, or the
+    /// empty string for a scalar port.
+    pub declared_range: &'static str,
+}
+
+/// The number of bits in a port declared with the range `[msb:lsb]`. The range
+/// may be descending (e.g., `[7:0]`) or ascending (e.g., `[0:7]`); either way,
+/// the leftmost bit is the most significant.
+pub fn port_width(msb: usize, lsb: usize) -> usize {
+    msb.abs_diff(lsb) + 1
+}
+
 /// Based off of the [C++ standards supported by GCC](https://gcc.gnu.org/projects/cxx-status.html) as
 /// of June 6th, 2025.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -117,6 +158,58 @@ pub struct VerilatedModelConfig {
 
     /// Optionally specify the C++ standard used by Verilator.
     pub cxx_standard: Option<CxxStandard>,
+
+    /// If `Some`, the flag `--unroll-count <count>` will be passed, raising
+    /// the maximum number of loop iterations Verilator will unroll.
+    pub unroll_count: Option<usize>,
+
+    /// Whether output printed by the model, such as from `$display`, should
+    /// start out suppressed. This is useful for high-throughput runs like
+    /// fuzzing. Printing can be toggled at runtime with `set_quiet` on the
+    /// model.
+    pub quiet: bool,
+
+    /// Whether `` `include `` directives should be resolved relative to the
+    /// directory of the including file first, which many vendor IP packages
+    /// assume. This passes the `--relative-includes` flag.
+    pub relative_includes: bool,
+
+    /// Directories in which to look for files opened for reading by the
+    /// model, such as by `$readmemh`/`$readmemb`, when a relative path does
+    /// not exist relative to the current working directory. They are searched
+    /// in order, and relative directories are resolved against the current
+    /// working directory when the model is built.
+    ///
+    /// This is only supported on Linux, since the search relies on the GNU
+    /// linker's `--wrap`; elsewhere, building a model with search paths fails.
+    pub readmem_search_paths: Vec<Utf8PathBuf>,
+
+    /// The kinds of coverage the model should collect, which can be written
+    /// out with `write_coverage` on the model. Each combination of kinds
+    /// builds a separate library. See [`coverage`] for details.
+    pub coverage: CoverageKinds,
+
+    /// Whether the model should be compiled and linked with link-time
+    /// optimization (`-flto`), which speeds up evaluation in long,
+    /// CPU-bound simulations at the cost of noticeably longer build times.
+    pub enable_lto: bool,
+
+    /// Module parameters to override, as `(name, value)` pairs each passed as
+    /// `-G<name>=<value>`. String values must include their own quotes, e.g.,
+    /// `"\"fast\""`. Port widths must not depend on overridden parameters.
+    pub parameters: Vec<(String, String)>,
+
+    /// Preprocessor macros to define, as `(name, value)` pairs each passed as
+    /// `+define+<name>` or, with a value, `+define+<name>=<value>`.
+    pub defines: Vec<(String, Option<String>)>,
+
+    /// Further arguments passed to Verilator as-is after all others, e.g.,
+    /// `--timing` or `--x-assign unique` (as two arguments), for options not
+    /// covered by this configuration. Like every other field, they contribute
+    /// to [`VerilatedModelConfig::cache_key_with`], so each set of arguments
+    /// builds a separate library. `--sc` is rejected, since Marlin requires
+    /// C++ output.
+    pub extra_verilator_args: Vec<String>,
 }
 
 impl Default for VerilatedModelConfig {
@@ -126,10 +219,52 @@ impl Default for VerilatedModelConfig {
             ignored_warnings: Default::default(),
             enable_tracing: Default::default(),
             cxx_standard: Some(CxxStandard::Cxx14),
+            unroll_count: None,
+            quiet: false,
+            relative_includes: false,
+            readmem_search_paths: vec![],
+            coverage: CoverageKinds::default(),
+            enable_lto: false,
+            parameters: vec![],
+            defines: vec![],
+            extra_verilator_args: vec![],
         }
     }
 }
 
+impl VerilatedModelConfig {
+    /// The hash identifying the library built for a model with this
+    /// configuration and signature `ports`, which also suffixes the name of its
+    /// subdirectory of the artifact directory. Every field of the
+    /// configuration and of each port contributes, so models of the same
+    /// module and source file share a library exactly when their keys agree,
+    /// e.g., even toggling [`VerilatedModelConfig::quiet`] builds another one.
+    ///
+    /// The key is stable within a build of Marlin but may change between
+    /// versions, so do not persist it.
+    pub fn cache_key_with(
+        &self,
+        ports: &[(&str, usize, usize, PortDirection)],
+    ) -> u64 {
+        let mut hasher = hash::DefaultHasher::new();
+        ports.hash(&mut hasher);
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether a model with this configuration would share its library with a
+    /// model of the same module and source file with the `other`
+    /// configuration, both with signature `ports`. See
+    /// [`VerilatedModelConfig::cache_key_with`].
+    pub fn shares_library_with(
+        &self,
+        other: &Self,
+        ports: &[(&str, usize, usize, PortDirection)],
+    ) -> bool {
+        self.cache_key_with(ports) == other.cache_key_with(ports)
+    }
+}
+
 /// You should not implement this `trait` manually. Instead, use a procedural
 /// macro like `#[verilog(...)]` to derive it for you.
 pub trait AsVerilatedModel<'ctx>: 'ctx {
@@ -142,13 +277,104 @@ pub trait AsVerilatedModel<'ctx>: 'ctx {
     /// The module's interface.
     fn ports() -> &'static [(&'static str, usize, usize, PortDirection)];
 
+    /// The module's interface, like [`AsVerilatedModel::ports`], but with the
+    /// range each port was declared with.
+    fn port_descriptors() -> &'static [PortDescriptor];
+
+    /// Module parameters the model type itself overrides, e.g., from const
+    /// generics. These are applied after
+    /// [`VerilatedModelConfig::parameters`].
+    fn parameters() -> Vec<(&'static str, String)> {
+        vec![]
+    }
+
+    /// Preprocessor macros the model type itself defines, e.g., from the
+    /// `define(...)` argument of `#[verilog]`. These are applied after
+    /// [`VerilatedModelConfig::defines`].
+    fn defines() -> Vec<(&'static str, Option<&'static str>)> {
+        vec![]
+    }
+
+    /// Fails if the library lacks a symbol the model needs, e.g., because it
+    /// is stale.
     #[doc(hidden)]
-    fn init_from(library: &'ctx Library, tracing_enabled: bool) -> Self;
+    fn init_from(
+        runtime: &'ctx VerilatorRuntime,
+        library: &'ctx Library,
+        tracing_enabled: bool,
+    ) -> Result<Self, Whatever>
+    where
+        Self: Sized;
 
     #[doc(hidden)]
     unsafe fn model(&self) -> *mut ffi::c_void;
 }
 
+/// A model that can be sent to another thread, e.g., to run it on a dedicated
+/// worker thread. Models are otherwise neither [`Send`] nor [`Sync`] because
+/// Verilated models and the [`VerilatorRuntime`] are not thread-safe.
+///
+/// The model is accessed through this wrapper, which dereferences to it.
+pub struct AssertSend<M>(M);
+
+// SAFETY: upheld by the caller of `AssertSend::new`
+unsafe impl<M> Send for AssertSend<M> {}
+
+impl<M> AssertSend<M> {
+    /// Wraps `model` so that it can be sent to another thread.
+    ///
+    /// # Safety
+    ///
+    /// The model shares state with the runtime that created it and with every
+    /// other model built from the same library. The caller must ensure that
+    /// none of these are ever used on different threads at the same time,
+    /// including when the model is dropped, which releases it back to the
+    /// runtime. The runtime must also outlive the model, e.g., by being leaked
+    /// as with [`quick_model`].
+    pub unsafe fn new(model: M) -> Self {
+        Self(model)
+    }
+
+    /// Unwraps the model.
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+}
+
+impl<M> Deref for AssertSend<M> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<M> DerefMut for AssertSend<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Identifies the file at `path` on disk independently of how the path is
+/// spelled, or `None` if it cannot be determined.
+#[cfg(unix)]
+fn file_identity(path: &Utf8Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+/// Identifies the file at `path` on disk independently of how the path is
+/// spelled, or `None` if it cannot be determined. Canonicalization on Windows
+/// already resolves the true spelling of a path, so there is nothing further to
+/// compare.
+#[cfg(not(unix))]
+fn file_identity(_path: &Utf8Path) -> Option<()> {
+    None
+}
+
 /// Optional configuration for creating a [`VerilatorRuntime`]. Usually, you can
 /// just use [`VerilatorRuntimeOptions::default()`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -163,6 +389,54 @@ pub struct VerilatorRuntimeOptions {
 
     /// Whether to use the log crate.
     pub log: bool,
+
+    /// Whether [`VerilatorRuntime::new`] should check that every source file
+    /// exists. Disabling this saves a filesystem query per source file, which
+    /// adds up for runtimes created with hundreds of sources. Only disable it
+    /// when you know the source list is valid.
+    pub validate_sources: bool,
+
+    /// If `Some`, the `VERILATOR_ROOT` environment variable is set to this
+    /// directory when invoking Verilator. Use this for relocated installs or
+    /// wrapper scripts where Verilator cannot find its own headers.
+    pub verilator_root: Option<Utf8PathBuf>,
+
+    /// Whether building a model should fail if Verilator emits any warnings.
+    /// See also [`VerilatorRuntime::build_warnings`].
+    pub treat_warnings_as_errors: bool,
+
+    /// Verilator file lists, each passed to Verilator with `-f`. The source
+    /// files they name are available to models just like the source files
+    /// passed to [`VerilatorRuntime::new`].
+    pub file_lists: Vec<Utf8PathBuf>,
+
+    /// Whether to print Verilator's output (including that of the C++
+    /// compiler) to standard error as it runs instead of only reporting it
+    /// when the build fails. This is useful for watching a slow build proceed.
+    pub stream_build_output: bool,
+
+    /// If `Some`, each built library is also copied into a subdirectory of
+    /// this directory and loaded from there. An up-to-date library in the
+    /// cache is used without rebuilding even if the artifact directory, which
+    /// holds the much larger intermediate build products, is missing. This
+    /// lets CI persist only the cache directory.
+    pub cache_directory: Option<Utf8PathBuf>,
+
+    /// Whether every model created by the runtime shares one Verilator
+    /// context instead of each having its own. Models in a shared context
+    /// share the simulation time (see [`VerilatorRuntime::shared_time`]), so
+    /// `$time` agrees across models when co-simulating them.
+    pub shared_context: bool,
+
+    /// Whether the libraries this runtime loads are shared with every other
+    /// runtime in the process that also enables this, so that a model built
+    /// by one is reused by the others instead of being checked and loaded
+    /// again, even from another artifact directory. A library is only shared
+    /// between runtimes with the same options, DPI functions, and source and
+    /// include directory contents, and shared libraries stay loaded until the
+    /// process exits. Models from a shared library share its global state,
+    /// such as [`DynamicVerilatedModel::set_quiet`].
+    pub share_libraries: bool,
 }
 
 impl Default for VerilatorRuntimeOptions {
@@ -171,6 +445,14 @@ impl Default for VerilatorRuntimeOptions {
             verilator_executable: "verilator".into(),
             force_verilator_rebuild: false,
             log: false,
+            validate_sources: true,
+            verilator_root: None,
+            treat_warnings_as_errors: false,
+            file_lists: vec![],
+            stream_build_output: false,
+            cache_directory: None,
+            shared_context: false,
+            share_libraries: false,
         }
     }
 }
@@ -186,6 +468,42 @@ impl VerilatorRuntimeOptions {
     }
 }
 
+/// The fully-resolved configuration a model is built with, as returned by
+/// [`VerilatorRuntime::effective_build_config`]. Comparing these between
+/// environments helps track down builds that differ between machines.
+///
+/// Besides [`EffectiveBuildConfig::defines`], defines may also come from the
+/// [`VerilatorRuntimeOptions::file_lists`], which are listed among the
+/// Verilator arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveBuildConfig {
+    /// The `verilator` executable that is invoked.
+    pub verilator_executable: OsString,
+
+    /// The `VERILATOR_ROOT` Verilator is invoked with, if overridden.
+    pub verilator_root: Option<Utf8PathBuf>,
+
+    /// The first line Verilator prints for `--version`.
+    pub verilator_version: String,
+
+    /// Every source file the model is built from, including those named in
+    /// file lists.
+    pub source_files: Vec<Utf8PathBuf>,
+
+    /// The directories searched for `` `include ``d files.
+    pub include_directories: Vec<Utf8PathBuf>,
+
+    /// The module parameters overridden for the model.
+    pub parameters: Vec<(String, String)>,
+
+    /// The preprocessor macros defined for the model.
+    pub defines: Vec<(String, Option<String>)>,
+
+    /// The exact arguments Verilator is invoked with. Relative paths like
+    /// `../ffi/ffi.cpp` are resolved against the `--Mdir` directory.
+    pub verilator_arguments: Vec<String>,
+}
+
 #[derive(PartialEq, Eq, Hash, Clone)]
 struct LibraryArenaKey {
     name: String,
@@ -193,23 +511,99 @@ struct LibraryArenaKey {
     hash: u64,
 }
 
+impl LibraryArenaKey {
+    fn new(
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            source_path: source_path.to_owned(),
+            hash: config.cache_key_with(ports),
+        }
+    }
+
+    /// The name of the subdirectory of the artifact directory in which this
+    /// library is built.
+    fn directory_name(&self) -> String {
+        format!(
+            "{}_{}_{}",
+            self.name,
+            self.source_path.replace("_", "__").replace("/", "_"),
+            self.hash
+        )
+    }
+}
+
+/// Identifies a library in [`SHARED_LIBRARIES`]. Besides the model itself, it
+/// covers everything else the runtime builds the library from.
+#[derive(PartialEq, Eq, Hash)]
+struct SharedLibraryKey {
+    library_key: LibraryArenaKey,
+    build_hash: u64,
+}
+
+/// The libraries loaded by runtimes created with
+/// [`VerilatorRuntimeOptions::share_libraries`].
+static SHARED_LIBRARIES: LazyLock<DashMap<SharedLibraryKey, Arc<Library>>> =
+    LazyLock::new(DashMap::default);
+
+/// The Verilator context shared by every model of a runtime created with
+/// [`VerilatorRuntimeOptions::shared_context`]. It is created by the library
+/// of the first model, and every library is built by the same Verilator, so
+/// the others agree on its layout.
+struct SharedContext {
+    context: *mut ffi::c_void,
+    delete: extern "C" fn(*mut ffi::c_void),
+    time: extern "C" fn(*mut ffi::c_void) -> u64,
+    set_time: extern "C" fn(*mut ffi::c_void, u64),
+    time_inc: extern "C" fn(*mut ffi::c_void, u64),
+    trace_ever_on: extern "C" fn(*mut ffi::c_void, bool),
+}
+
 /// Runtime for (System)Verilog code.
 pub struct VerilatorRuntime {
     artifact_directory: Utf8PathBuf,
     source_files: Vec<Utf8PathBuf>,
+    /// The source files named in `options.file_lists`.
+    file_list_sources: Vec<Utf8PathBuf>,
+    /// The canonicalized `source_files` and `file_list_sources`, computed on
+    /// the first model creation and reused for validating every model's
+    /// source path afterward.
+    canonical_source_files: OnceCell<Vec<Utf8PathBuf>>,
     include_directories: Vec<Utf8PathBuf>,
     dpi_functions: Vec<&'static dyn DpiFunction>,
     options: VerilatorRuntimeOptions,
     /// Mapping between hardware (top, path) and arena index of Verilator
     /// implementations
     library_map: RefCell<HashMap<LibraryArenaKey, usize>>,
-    /// Verilator implementations arena
-    library_arena: BoxcarVec<Library>,
-    /// SAFETY: These are dropped when the runtime is dropped. They will not be
-    /// "borrowed mutably" because the models created for this runtime must
-    /// not outlive it and thus will be all gone before these are dropped.
+    /// Verilator implementations arena, some of which may also be in
+    /// [`SHARED_LIBRARIES`]
+    library_arena: BoxcarVec<Arc<Library>>,
+    /// Models remove and free their own entry when they are dropped (see
+    /// [`VerilatorRuntime::release_model`]), so these are only the models that
+    /// were never dropped, e.g., because they were leaked. SAFETY: These are
+    /// dropped when the runtime is dropped. They will not be "borrowed
+    /// mutably" because the models created for this runtime must not outlive
+    /// it and thus will be all gone before these are dropped.
     model_deallocators:
         RefCell<Vec<(*mut ffi::c_void, extern "C" fn(*mut ffi::c_void))>>,
+    /// The `final` FFI wrappers for each model created with this runtime that
+    /// has not been finalized yet, used by [`VerilatorRuntime::finalize`].
+    /// SAFETY: see `model_deallocators`.
+    model_finalizers:
+        RefCell<Vec<(*mut ffi::c_void, extern "C" fn(*mut ffi::c_void))>>,
+    /// See [`VerilatorRuntime::build_warnings`].
+    build_warnings: RefCell<Vec<String>>,
+    /// The canonicalized files written by
+    /// [`VerilatorRuntime::create_dyn_model_from_source`], each of which is
+    /// built on its own rather than with `source_files`.
+    inline_source_files: RefCell<Vec<Utf8PathBuf>>,
+    /// See [`VerilatorRuntimeOptions::shared_context`]. SAFETY: the models
+    /// using the context are freed before it is when the runtime is dropped.
+    shared_context: OnceCell<SharedContext>,
 }
 
 impl Drop for VerilatorRuntime {
@@ -219,24 +613,22 @@ impl Drop for VerilatorRuntime {
         {
             deallocator(model);
         }
+        if let Some(shared_context) = self.shared_context.take() {
+            (shared_context.delete)(shared_context.context);
+        }
     }
 }
 
 /* <Forgive me father for I have sinned> */
 
-// TODO: make cross-platform
-static STDERR: LazyLock<Mutex<fs::File>> =
-    LazyLock::new(|| Mutex::new(unsafe { fs::File::from_raw_fd(2) }));
-
+// the test harness only captures the printing macros, so writing to the
+// (process-wide, locked) stderr handle directly still reaches the terminal
 macro_rules! eprintln_nocapture {
     ($($contents:tt)*) => {{
         use snafu::ResultExt;
 
-        writeln!(
-            &mut STDERR.lock().expect("poisoned"),
-            $($contents)*
-        )
-        .whatever_context("Failed to write to non-captured stderr")
+        writeln!(std::io::stderr().lock(), $($contents)*)
+            .whatever_context("Failed to write to non-captured stderr")
     }};
 }
 
@@ -245,9 +637,10 @@ struct ThreadLocalFileLock;
 
 /// The file_guard handles locking across processes, but does not guarantee
 /// locking between threads in one process. Thus, we have this lock to
-/// synchronize threads for a given artifacts directoru.
+/// synchronize threads for a given artifacts directoru. Only builds into the
+/// same artifacts subdirectory, i.e., of the same library, wait on each other.
 static THREAD_LOCKS_PER_BUILD_DIR: LazyLock<
-    DashMap<Utf8PathBuf, Mutex<ThreadLocalFileLock>>,
+    DashMap<Utf8PathBuf, Arc<Mutex<ThreadLocalFileLock>>>,
 > = LazyLock::new(DashMap::default);
 
 /* </Forgive me father for I have sinned> */
@@ -259,20 +652,24 @@ fn one_time_library_setup(
     options: &VerilatorRuntimeOptions,
 ) -> Result<(), Whatever> {
     if !dpi_functions.is_empty() {
-        let dpi_init_callback: extern "C" fn(*const *const ffi::c_void) =
-            *unsafe { library.get(b"dpi_init_callback") }
-                .whatever_context("Failed to load DPI initializer")?;
-
-        // order is important here. the function pointers will be
-        // initialized in the same order that they
-        // appear in the DPI array --- this is to match how the C
-        // initialization code was constructed in `build_library`.
-        let function_pointers = dpi_functions
-            .iter()
-            .map(|dpi_function| dpi_function.pointer())
-            .collect::<Vec<_>>();
-
-        (dpi_init_callback)(function_pointers.as_ptr_range().start);
+        let dpi_init_callback: extern "C" fn(
+            *const ffi::c_char,
+            *const ffi::c_void,
+        ) -> bool = *unsafe { library.get(b"dpi_init_callback") }
+            .whatever_context("Failed to load DPI initializer")?;
+
+        // each function pointer is bound by name, so the order of the DPI
+        // array need not match the order the C bindings were generated in
+        for dpi_function in dpi_functions {
+            let name = ffi::CString::new(dpi_function.name())
+                .whatever_context("Failed to convert DPI name to C string")?;
+            if !(dpi_init_callback)(name.as_ptr(), dpi_function.pointer()) {
+                whatever!(
+                    "DPI function {} is not bound by the compiled library",
+                    dpi_function.name()
+                );
+            }
+        }
 
         if options.log {
             log::info!("Initialized DPI functions");
@@ -305,15 +702,26 @@ impl VerilatorRuntime {
         dpi_functions: impl IntoIterator<Item = &'static dyn DpiFunction>,
         options: VerilatorRuntimeOptions,
     ) -> Result<Self, Whatever> {
-        if options.log {
-            log::info!("Validating source files");
+        let mut file_list_sources = vec![];
+        for file_list in &options.file_lists {
+            file_list_sources.extend(file_list::read_file_list(file_list)?);
         }
-        for source_file in source_files {
-            if !source_file.is_file() {
-                whatever!(
-                    "Source file {} does not exist or is not a file. Note that if it's a relative path, you must be in the correct directory",
-                    source_file
-                );
+
+        if options.validate_sources {
+            if options.log {
+                log::info!("Validating source files");
+            }
+            for source_file in source_files
+                .iter()
+                .copied()
+                .chain(file_list_sources.iter().map(Utf8PathBuf::as_path))
+            {
+                if !source_file.is_file() {
+                    whatever!(
+                        "Source file {} does not exist or is not a file. Note that if it's a relative path, you must be in the correct directory",
+                        source_file
+                    );
+                }
             }
         }
 
@@ -323,6 +731,8 @@ impl VerilatorRuntime {
                 .iter()
                 .map(|path| path.to_path_buf())
                 .collect(),
+            file_list_sources,
+            canonical_source_files: OnceCell::new(),
             include_directories: include_directories
                 .iter()
                 .map(|path| path.to_path_buf())
@@ -332,6 +742,10 @@ impl VerilatorRuntime {
             library_map: RefCell::new(HashMap::new()),
             library_arena: BoxcarVec::new(),
             model_deallocators: RefCell::new(vec![]),
+            model_finalizers: RefCell::new(vec![]),
+            build_warnings: RefCell::new(vec![]),
+            inline_source_files: RefCell::new(vec![]),
+            shared_context: OnceCell::new(),
         })
     }
 
@@ -353,6 +767,25 @@ impl VerilatorRuntime {
         &'ctx self,
         config: &VerilatedModelConfig,
     ) -> Result<M, Whatever> {
+        let type_parameters = M::parameters();
+        let type_defines = M::defines();
+        let parameterized_config;
+        let config = if type_parameters.is_empty() && type_defines.is_empty() {
+            config
+        } else {
+            let mut config = config.clone();
+            config.parameters.extend(
+                type_parameters
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value)),
+            );
+            config.defines.extend(type_defines.into_iter().map(
+                |(name, value)| (name.to_string(), value.map(str::to_string)),
+            ));
+            parameterized_config = config;
+            &parameterized_config
+        };
+
         let library = self
             .build_or_retrieve_library(
                 M::name(),
@@ -367,15 +800,30 @@ impl VerilatorRuntime {
         let delete_model: extern "C" fn(*mut ffi::c_void) = *unsafe {
             library.get(format!("ffi_delete_V{}", M::name()).as_bytes())
         }
-        .expect("failed to get symbol");
+        .whatever_context(format!(
+            "Failed to load destructor for module {}",
+            M::name()
+        ))?;
+
+        let final_model: extern "C" fn(*mut ffi::c_void) = *unsafe {
+            library.get(format!("ffi_V{}_final", M::name()).as_bytes())
+        }
+        .whatever_context(format!(
+            "Failed to load finalizer for module {}",
+            M::name()
+        ))?;
 
-        let model = M::init_from(library, config.enable_tracing);
+        self.initialize_shared_context(library, config.enable_tracing)?;
+        let model = M::init_from(self, library, config.enable_tracing)?;
 
         self.model_deallocators.borrow_mut().push((
             // SAFETY: todo
             unsafe { model.model() },
             delete_model,
         ));
+        self.model_finalizers
+            .borrow_mut()
+            .push((unsafe { model.model() }, final_model));
 
         Ok(model)
     }
@@ -422,7 +870,7 @@ impl VerilatorRuntime {
                 "Failed to build or retrieve verilator dynamic library. Try removing the build directory if it is corrupted.",
             )?;
 
-        let new_main: extern "C" fn() -> *mut ffi::c_void =
+        let new_main: extern "C" fn(*mut ffi::c_void) -> *mut ffi::c_void =
             *unsafe { library.get(format!("ffi_new_V{name}").as_bytes()) }
                 .whatever_context(format!(
                     "Failed to load constructor for module {name}"
@@ -437,30 +885,542 @@ impl VerilatorRuntime {
                 .whatever_context(format!(
                     "Failed to load evalulator for module {name}"
                 ))?;
+        let final_main =
+            *unsafe { library.get(format!("ffi_V{name}_final").as_bytes()) }
+                .whatever_context(format!(
+                    "Failed to load finalizer for module {name}"
+                ))?;
+        let finish_code_main = *unsafe {
+            library.get(format!("ffi_V{name}_finish_code").as_bytes())
+        }
+        .whatever_context(format!(
+            "Failed to load finish code getter for module {name}"
+        ))?;
+        let set_quiet_main = *unsafe { library.get(b"ffi_marlin_set_quiet") }
+            .whatever_context(format!(
+            "Failed to load output silencer for module {name}"
+        ))?;
 
-        let main = new_main();
+        self.initialize_shared_context(library, config.enable_tracing)?;
+        let main = new_main(self.shared_context_pointer());
 
         let ports = ports
             .iter()
             .copied()
             .map(|(port, high, low, direction)| {
-                (port.to_string(), (high - low + 1, direction))
+                (port.to_string(), (port_width(high, low), direction, false))
             })
             .collect();
 
         self.model_deallocators
             .borrow_mut()
             .push((main, delete_main));
+        self.model_finalizers.borrow_mut().push((main, final_main));
 
         Ok(DynamicVerilatedModel {
             ports,
             name: name.to_string(),
             main,
             eval_main,
+            final_main,
+            finish_code_main,
+            set_quiet_main,
             library,
+            runtime: self,
+            clock_port: None,
+            reset_port: None,
+            driven_inputs: HashSet::new(),
+            post_reset_inputs: vec![],
+            cancelled: Arc::new(AtomicBool::new(false)),
+            on_eval: None,
+        })
+    }
+
+    /// Like [`VerilatorRuntime::create_dyn_model`], but the Verilog module
+    /// `name` is defined in the in-memory Verilog `source` instead of a file
+    /// given to the runtime, e.g., to synthesize small modules on the fly in
+    /// property tests.
+    ///
+    /// The source is written to a file under the artifact directory named
+    /// after its contents, so creating a model from the same source again
+    /// reuses the library built for it. The model is built only from `source`
+    /// (and any file lists in the runtime options), not from the source files
+    /// given to [`VerilatorRuntime::new`].
+    pub fn create_dyn_model_from_source<'ctx>(
+        &'ctx self,
+        name: &str,
+        source: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: VerilatedModelConfig,
+    ) -> Result<DynamicVerilatedModel<'ctx>, Whatever> {
+        let mut hasher = hash::DefaultHasher::new();
+        source.hash(&mut hasher);
+        let inline_source_directory = self.artifact_directory.join("inline");
+        let source_path = inline_source_directory
+            .join(format!("{name}_{}.sv", hasher.finish()));
+
+        // there is no need to rewrite identical contents
+        if fs::read_to_string(&source_path)
+            .map_or(true, |existing_source| existing_source != source)
+        {
+            fs::create_dir_all(&inline_source_directory).whatever_context(
+                format!(
+                    "Failed to create directory {inline_source_directory} for in-memory sources"
+                ),
+            )?;
+            fs::write(&source_path, source).whatever_context(format!(
+                "Failed to write in-memory source to {source_path}"
+            ))?;
+        }
+
+        let canonical_source_path =
+            source_path.canonicalize_utf8().whatever_context(format!(
+                "Failed to canonicalize in-memory source path {source_path}"
+            ))?;
+        {
+            let mut inline_source_files = self.inline_source_files.borrow_mut();
+            if !inline_source_files.contains(&canonical_source_path) {
+                inline_source_files.push(canonical_source_path);
+            }
+        }
+
+        self.create_dyn_model(name, source_path.as_str(), ports, config)
+    }
+
+    /// Creates the shared Verilator context with `library` if the runtime
+    /// should have one and it does not exist yet. If `tracing_enabled`, the
+    /// context is also configured for tracing, which `Verilated::traceEverOn`
+    /// only does for the default context.
+    fn initialize_shared_context(
+        &self,
+        library: &Library,
+        tracing_enabled: bool,
+    ) -> Result<(), Whatever> {
+        if !self.options.shared_context {
+            return Ok(());
+        }
+
+        if self.shared_context.get().is_none() {
+            let new_context: extern "C" fn() -> *mut ffi::c_void =
+                *unsafe { library.get(b"ffi_new_VerilatedContext") }
+                    .whatever_context("Failed to load context constructor")?;
+            let delete =
+                *unsafe { library.get(b"ffi_delete_VerilatedContext") }
+                    .whatever_context("Failed to load context destructor")?;
+            let time = *unsafe { library.get(b"ffi_VerilatedContext_time") }
+                .whatever_context("Failed to load context time getter")?;
+            let set_time =
+                *unsafe { library.get(b"ffi_VerilatedContext_set_time") }
+                    .whatever_context("Failed to load context time setter")?;
+            let time_inc =
+                *unsafe { library.get(b"ffi_VerilatedContext_timeInc") }
+                    .whatever_context(
+                        "Failed to load context time incrementer",
+                    )?;
+            let trace_ever_on =
+                *unsafe { library.get(b"ffi_VerilatedContext_traceEverOn") }
+                    .whatever_context(
+                        "Failed to load context tracing initializer",
+                    )?;
+            let _ = self.shared_context.set(SharedContext {
+                context: new_context(),
+                delete,
+                time,
+                set_time,
+                time_inc,
+                trace_ever_on,
+            });
+
+            if self.options.log {
+                log::info!("Created shared Verilator context");
+            }
+        }
+
+        if tracing_enabled {
+            let shared_context =
+                self.shared_context.get().expect("initialized above");
+            (shared_context.trace_ever_on)(shared_context.context, true);
+        }
+
+        Ok(())
+    }
+
+    /// The shared Verilator context models should be constructed in, or null
+    /// if each should have its own.
+    #[doc(hidden)]
+    pub fn shared_context_pointer(&self) -> *mut ffi::c_void {
+        self.shared_context
+            .get()
+            .map_or(std::ptr::null_mut(), |shared_context| {
+                shared_context.context
+            })
+    }
+
+    /// The simulation time of the context shared by every model, in units of
+    /// the design's time precision. Returns `None` if the runtime was not
+    /// created with [`VerilatorRuntimeOptions::shared_context`] or no model
+    /// has been created yet.
+    pub fn shared_time(&self) -> Option<u64> {
+        self.shared_context
+            .get()
+            .map(|shared_context| (shared_context.time)(shared_context.context))
+    }
+
+    /// Sets the simulation time of the context shared by every model, e.g.,
+    /// to the timestamp passed to [`vcd::Vcd::dump`] so that `$time` matches
+    /// the trace. Time should only move forward. Fails if there is no shared
+    /// context (see [`VerilatorRuntime::shared_time`]).
+    pub fn set_shared_time(&self, time: u64) -> Result<(), Whatever> {
+        let Some(shared_context) = self.shared_context.get() else {
+            whatever!(
+                "Runtime has no shared context: did you forget to set `VerilatorRuntimeOptions::shared_context` or create a model first?"
+            );
+        };
+        (shared_context.set_time)(shared_context.context, time);
+        Ok(())
+    }
+
+    /// Advances the simulation time of the context shared by every model by
+    /// `delta`, like `timeInc` on a `VerilatedContext`, so that models stepped
+    /// together agree on `$time`. Fails if there is no shared context (see
+    /// [`VerilatorRuntime::shared_time`]).
+    pub fn advance_shared_time(&self, delta: u64) -> Result<(), Whatever> {
+        let Some(shared_context) = self.shared_context.get() else {
+            whatever!(
+                "Runtime has no shared context: did you forget to set `VerilatorRuntimeOptions::shared_context` or create a model first?"
+            );
+        };
+        (shared_context.time_inc)(shared_context.context, delta);
+        Ok(())
+    }
+
+    /// The `%Warning` lines Verilator printed while building models for this
+    /// runtime. Only builds that actually invoke Verilator contribute, so
+    /// models whose libraries were already up to date report nothing.
+    pub fn build_warnings(&self) -> Vec<String> {
+        self.build_warnings.borrow().clone()
+    }
+
+    /// Calls the Verilator `final` method on every model created with this
+    /// runtime that has not been dropped yet, running the `final` blocks in
+    /// the Verilog source code (e.g., end-of-simulation checks and coverage
+    /// flushing). Verilator never runs them on its own, including when a model
+    /// is dropped.
+    ///
+    /// The `final` blocks of each model run at most once, so models already
+    /// finalized, whether by an earlier call or by their own `finalize`, are
+    /// skipped. This does not flush VCDs; use `finalize` on the model itself
+    /// or [`vcd::Vcd::flush`] for that.
+    pub fn finalize(&self) {
+        // taken first so that the list is not borrowed while the `final`
+        // blocks run
+        let model_finalizers = self.model_finalizers.take();
+        for (model, finalizer) in model_finalizers {
+            finalizer(model);
+        }
+    }
+
+    /// Removes `model` from the models whose `final` blocks
+    /// [`VerilatorRuntime::finalize`] runs, returning whether it was still
+    /// there, i.e., whether its `final` blocks have yet to run. Models call
+    /// this before running their own `final` blocks.
+    #[doc(hidden)]
+    pub fn mark_finalized(&self, model: *mut ffi::c_void) -> bool {
+        let mut model_finalizers = self.model_finalizers.borrow_mut();
+        let length = model_finalizers.len();
+        model_finalizers.retain(|(other_model, _)| *other_model != model);
+        model_finalizers.len() != length
+    }
+
+    /// Frees the Verilator model `model`, which must have been created by this
+    /// runtime and not already released, so that the runtime neither
+    /// finalizes nor frees it later. Models call this when they are dropped.
+    ///
+    /// # Safety
+    ///
+    /// `model` must not be used afterward.
+    #[doc(hidden)]
+    pub unsafe fn release_model(&self, model: *mut ffi::c_void) {
+        self.model_finalizers
+            .borrow_mut()
+            .retain(|(other_model, _)| *other_model != model);
+
+        let deallocator = {
+            let mut model_deallocators = self.model_deallocators.borrow_mut();
+            model_deallocators
+                .iter()
+                .position(|(other_model, _)| *other_model == model)
+                .map(|index| model_deallocators.swap_remove(index).1)
+        };
+        if let Some(deallocator) = deallocator {
+            deallocator(model);
+        }
+    }
+
+    /// Enumerates the scopes and signals inside the model for the Verilog
+    /// module `name` defined in `source_path` with signature `ports`, as
+    /// reported by the headers Verilator generated when building it. The
+    /// arguments must match those used to create the model (see
+    /// [`VerilatorRuntime::create_dyn_model`]), and the model must have been
+    /// created at least once with the same artifact directory.
+    ///
+    /// Verilator only keeps internal signals it could not optimize away, so
+    /// consider disabling optimization or marking signals `/*verilator
+    /// public*/` if you are looking for a particular one.
+    pub fn model_hierarchy(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Result<ModelHierarchy, Whatever> {
+        let library_key =
+            LibraryArenaKey::new(name, source_path, ports, config);
+        hierarchy::read_hierarchy(&self.obj_dir(&library_key), name)
+    }
+
+    /// Builds the library for the Verilog module `name` defined in
+    /// `source_path` with signature `ports` ahead of time, without creating a
+    /// model, e.g., from a build script so that the first
+    /// [`VerilatorRuntime::create_dyn_model`] in a test is a cache hit.
+    ///
+    /// Libraries are found by the artifact directory and the exact arguments,
+    /// so the runtime creating the model must have the same artifact directory
+    /// (which, if relative, both build scripts and tests resolve against the
+    /// manifest directory), source files, and DPI functions, and must pass the
+    /// same arguments. For a model created with
+    /// [`VerilatorRuntime::create_model`], those are its
+    /// [`AsVerilatedModel::ports`] and `config` with its
+    /// [`AsVerilatedModel::parameters`] and [`AsVerilatedModel::defines`]
+    /// appended.
+    ///
+    /// ```no_run
+    /// // build.rs
+    /// # use marlin_verilator::*;
+    /// # || -> Result<(), snafu::Whatever> {
+    /// println!("cargo::rerun-if-changed=src/main.sv");
+    /// let runtime = VerilatorRuntime::new(
+    ///     "artifacts".into(),
+    ///     &["src/main.sv".as_ref()],
+    ///     &[],
+    ///     [],
+    ///     Default::default(),
+    /// )?;
+    /// runtime.precompile(
+    ///     "main",
+    ///     "src/main.sv",
+    ///     &[
+    ///         ("medium_input", 31, 0, PortDirection::Input),
+    ///         ("medium_output", 31, 0, PortDirection::Output),
+    ///     ],
+    ///     &VerilatedModelConfig::default(),
+    /// )?;
+    /// # Ok(()) };
+    /// ```
+    pub fn precompile(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Result<(), Whatever> {
+        self.build_or_retrieve_library(name, source_path, ports, config)
+            .whatever_context(
+                "Failed to build or retrieve verilator dynamic library. Try removing the build directory if it is corrupted.",
+            )?;
+        Ok(())
+    }
+
+    /// The path of the dynamic library built for the Verilog module `name`
+    /// defined in `source_path` with signature `ports`, e.g., to copy it or
+    /// inspect it with `nm`. The arguments must match those used to create the
+    /// model (see [`VerilatorRuntime::create_dyn_model`]). If the runtime has a
+    /// [`VerilatorRuntimeOptions::cache_directory`], this is the copy in the
+    /// cache. Returns `None` if the library has not been built in this
+    /// runtime's artifact or cache directory.
+    pub fn library_path_for(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Option<Utf8PathBuf> {
+        let library_key =
+            LibraryArenaKey::new(name, source_path, ports, config);
+        if let Some(cache_directory) = &self.options.cache_directory {
+            let cached_library_path = build_library::cached_library_path(
+                &cache_directory.join(library_key.directory_name()),
+                name,
+            );
+            if cached_library_path.is_file() {
+                return Some(cached_library_path);
+            }
+        }
+        let library_path = build_library::library_path(
+            &self.artifact_directory.join(library_key.directory_name()),
+            name,
+        );
+        library_path.is_file().then_some(library_path)
+    }
+
+    /// The build configuration of the model for the Verilog module `name`
+    /// defined in `source_path` with signature `ports`, resolved from this
+    /// runtime's options and `config`, e.g., to compare with the configuration
+    /// in another environment. The arguments must match those used to create
+    /// the model (see [`VerilatorRuntime::create_dyn_model`]), but the model
+    /// need not have been built. This invokes Verilator to query its version.
+    pub fn effective_build_config(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Result<EffectiveBuildConfig, Whatever> {
+        let inline_source_file = self.inline_source_file(source_path);
+        let (source_files, file_list_sources) =
+            self.model_source_files(&inline_source_file);
+        let library_key =
+            LibraryArenaKey::new(name, source_path, ports, config);
+
+        Ok(EffectiveBuildConfig {
+            verilator_executable: self.options.verilator_executable.clone(),
+            verilator_root: self.options.verilator_root.clone(),
+            verilator_version: build_library::verilator_version(&self.options)?,
+            source_files: [source_files, file_list_sources].concat(),
+            include_directories: self.include_directories.clone(),
+            parameters: config.parameters.clone(),
+            defines: config.defines.clone(),
+            verilator_arguments: build_library::verilator_arguments(
+                source_files,
+                &self.include_directories,
+                (!self.dpi_functions.is_empty())
+                    .then_some(Utf8Path::new(build_library::DPI_FILE)),
+                name,
+                &self.obj_dir(&library_key),
+                &self.options,
+                config,
+            )?,
         })
     }
 
+    /// The canonicalized `source_path` if it was written by
+    /// [`VerilatorRuntime::create_dyn_model_from_source`].
+    fn inline_source_file(&self, source_path: &str) -> Option<Utf8PathBuf> {
+        Utf8Path::new(source_path).canonicalize_utf8().ok().filter(
+            |source_path| {
+                self.inline_source_files.borrow().contains(source_path)
+            },
+        )
+    }
+
+    /// Hashes everything besides the model itself that the runtime builds a
+    /// library from, for [`SharedLibraryKey`].
+    fn build_hash(
+        &self,
+        inline_source_file: &Option<Utf8PathBuf>,
+    ) -> Result<u64, Whatever> {
+        let (source_files, file_list_sources) =
+            self.model_source_files(inline_source_file);
+        let mut hasher = hash::DefaultHasher::new();
+        build_library::source_hash(
+            &[source_files, file_list_sources, &self.options.file_lists]
+                .concat(),
+            &self.include_directories,
+        )
+        .whatever_context("Failed to hash source files")?
+        .hash(&mut hasher);
+        self.include_directories.hash(&mut hasher);
+        for dpi_function in &self.dpi_functions {
+            dpi_function.name().hash(&mut hasher);
+            dpi_function.pointer().hash(&mut hasher);
+        }
+        self.options.hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+
+    /// The source files and file list sources a model is built from: only its
+    /// `inline_source_file` if it has one, or else every source file given to
+    /// the runtime.
+    fn model_source_files<'a>(
+        &'a self,
+        inline_source_file: &'a Option<Utf8PathBuf>,
+    ) -> (&'a [Utf8PathBuf], &'a [Utf8PathBuf]) {
+        match inline_source_file {
+            Some(inline_source_file) => {
+                (slice::from_ref(inline_source_file), &[][..])
+            }
+            None => (
+                self.source_files.as_slice(),
+                self.file_list_sources.as_slice(),
+            ),
+        }
+    }
+
+    /// The directory in which Verilator generated the C++ model for the Verilog
+    /// module `name` defined in `source_path` with signature `ports`, e.g., to
+    /// point a debugger at `V<name>.cpp` and the headers next to it. The
+    /// arguments must match those used to create the model (see
+    /// [`VerilatorRuntime::create_dyn_model`]). Returns `None` if the model has
+    /// not been built in this runtime's artifact directory, which is the case
+    /// when its library was taken from the
+    /// [`VerilatorRuntimeOptions::cache_directory`] without building it.
+    pub fn obj_dir_for(
+        &self,
+        name: &str,
+        source_path: &str,
+        ports: &[(&str, usize, usize, PortDirection)],
+        config: &VerilatedModelConfig,
+    ) -> Option<Utf8PathBuf> {
+        let obj_dir = self.obj_dir(&LibraryArenaKey::new(
+            name,
+            source_path,
+            ports,
+            config,
+        ));
+        obj_dir.is_dir().then_some(obj_dir)
+    }
+
+    /// Lists every source file given to the runtime for error messages, along
+    /// with its canonical path where that differs, or `none`.
+    fn describe_source_files(&self) -> String {
+        let source_files = self
+            .source_files
+            .iter()
+            .chain(&self.file_list_sources)
+            .map(|source_file| match source_file.canonicalize_utf8() {
+                Ok(canonical) if canonical != *source_file => {
+                    format!("{source_file} (canonically {canonical})")
+                }
+                Ok(_) => source_file.to_string(),
+                Err(_) => {
+                    format!("{source_file} (which could not be canonicalized)")
+                }
+            })
+            .collect::<Vec<_>>();
+        if source_files.is_empty() {
+            "none".into()
+        } else {
+            source_files.join(", ")
+        }
+    }
+
+    /// The directory Verilator builds the library for `library_key` in.
+    fn obj_dir(&self, library_key: &LibraryArenaKey) -> Utf8PathBuf {
+        self.artifact_directory
+            .join(library_key.directory_name())
+            .join("obj_dir")
+    }
+
+    /// The C++ code binding this runtime's DPI functions for the Verilog
+    /// module `top_module`, exactly as it is written to `dpi.cpp` when building
+    /// the module, e.g., to check the C types against the `import "DPI-C"`
+    /// declarations in the Verilog source. Returns `None` if the runtime has
+    /// no DPI functions.
+    pub fn dpi_c_code(&self, top_module: &str) -> Option<String> {
+        (!self.dpi_functions.is_empty())
+            .then(|| build_library::dpi_c_code(top_module, &self.dpi_functions))
+    }
+
     /// Invokes verilator to build a dynamic library for the Verilog module
     /// named `name` defined in the file `source_path` and with signature
     /// `ports`.
@@ -500,49 +1460,56 @@ impl VerilatorRuntime {
         if self.options.log {
             log::info!("Validating model source file");
         }
-        if !self.source_files.iter().any(|source_file| {
-            match (
-                source_file.canonicalize_utf8(),
-                Utf8Path::new(source_path).canonicalize_utf8(),
-            ) {
-                (Ok(lhs), Ok(rhs)) => lhs == rhs,
-                _ => false,
+        let canonical_source_files =
+            self.canonical_source_files.get_or_init(|| {
+                self.source_files
+                    .iter()
+                    .chain(&self.file_list_sources)
+                    .filter_map(|source_file| {
+                        source_file.canonicalize_utf8().ok()
+                    })
+                    .collect()
+            });
+        let canonical_source_path =
+            Utf8Path::new(source_path).canonicalize_utf8();
+        let inline_source_file = self.inline_source_file(source_path);
+        if inline_source_file.is_none() {
+            let canonical_source_path = match &canonical_source_path {
+                Ok(canonical_source_path) => canonical_source_path,
+                Err(error) => {
+                    whatever!(
+                        "Module `{}` requires source file {}, which could not be canonicalized: {}. The runtime was provided: {}",
+                        name,
+                        source_path,
+                        error,
+                        self.describe_source_files()
+                    );
+                }
+            };
+            // on case-insensitive filesystems, canonicalization need not
+            // normalize case, so fall back to comparing file identities
+            let is_provided = canonical_source_files
+                .contains(canonical_source_path)
+                || file_identity(canonical_source_path).is_some_and(
+                    |identity| {
+                        canonical_source_files.iter().any(|source_file| {
+                            file_identity(source_file) == Some(identity)
+                        })
+                    },
+                );
+            if !is_provided {
+                whatever!(
+                    "Module `{}` requires source file {} (canonically {}), which was not provided to the runtime. The runtime was provided: {}",
+                    name,
+                    source_path,
+                    canonical_source_path,
+                    self.describe_source_files()
+                );
             }
-        }) {
-            whatever!(
-                "Module `{}` requires source file {}, which was not provided to the runtime",
-                name,
-                source_path
-            );
         }
 
-        if let Some((port, _, _, _)) =
-            ports.iter().find(|(_, high, low, _)| high < low)
-        {
-            whatever!(
-                "Port {} on module {} was specified with the high bit less than the low bit",
-                port,
-                name
-            );
-        }
-        if let Some((port, _, _, _)) =
-            ports.iter().find(|(_, high, low, _)| high + 1 - low > 64)
-        {
-            whatever!(
-                "Port {} on module {} is greater than 64 bits",
-                port,
-                name
-            );
-        }
-
-        let mut hasher = hash::DefaultHasher::new();
-        ports.hash(&mut hasher);
-        config.hash(&mut hasher);
-        let library_key = LibraryArenaKey {
-            name: name.to_owned(),
-            source_path: source_path.to_owned(),
-            hash: hasher.finish(),
-        };
+        let library_key =
+            LibraryArenaKey::new(name, source_path, ports, config);
 
         let library_idx = match self
             .library_map
@@ -551,11 +1518,34 @@ impl VerilatorRuntime {
         {
             Entry::Occupied(entry) => *entry.get(),
             Entry::Vacant(entry) => {
-                let local_directory_name = format!(
-                    "{name}_{}_{}",
-                    source_path.replace("_", "__").replace("/", "_"),
-                    library_key.hash
-                );
+                let shared_library_key = if self.options.share_libraries {
+                    Some(SharedLibraryKey {
+                        library_key: library_key.clone(),
+                        build_hash: self.build_hash(&inline_source_file)?,
+                    })
+                } else {
+                    None
+                };
+                if let Some(shared_library) = shared_library_key
+                    .as_ref()
+                    .filter(|_| !self.options.force_verilator_rebuild)
+                    .and_then(|key| SHARED_LIBRARIES.get(key))
+                {
+                    if self.options.log {
+                        log::info!(
+                            "Reusing the dynamic library of another runtime"
+                        );
+                    }
+                    let library_idx =
+                        self.library_arena.push(shared_library.clone());
+                    entry.insert(library_idx);
+                    return Ok(self
+                        .library_arena
+                        .get(library_idx)
+                        .expect("bug: We just inserted the library"));
+                }
+
+                let local_directory_name = library_key.directory_name();
                 let local_artifacts_directory =
                     self.artifact_directory.join(&local_directory_name);
 
@@ -575,17 +1565,13 @@ impl VerilatorRuntime {
                 //    std::thread::current().id()
                 //)?;
 
-                if !THREAD_LOCKS_PER_BUILD_DIR
-                    .contains_key(&local_artifacts_directory)
-                {
-                    THREAD_LOCKS_PER_BUILD_DIR.insert(
-                        local_artifacts_directory.clone(),
-                        Default::default(),
-                    );
-                }
+                // the lock is cloned out so that the map is not borrowed
+                // during the build, which would block threads building other
+                // libraries whose directories share a shard of the map
                 let thread_mutex = THREAD_LOCKS_PER_BUILD_DIR
-                    .get(&local_artifacts_directory)
-                    .expect("We just inserted if it didn't exist");
+                    .entry(local_artifacts_directory.clone())
+                    .or_default()
+                    .clone();
 
                 let _thread_lock = if let Ok(_thread_lock) =
                     thread_mutex.try_lock()
@@ -643,13 +1629,23 @@ impl VerilatorRuntime {
                 if self.options.log {
                     log::info!("Building the dynamic library with verilator");
                 }
-                let (library_path, was_rebuilt) = build_library(
-                    &self.source_files,
+                let (source_files, file_list_sources) =
+                    self.model_source_files(&inline_source_file);
+                let (library_path, was_rebuilt, warnings) = build_library(
+                    source_files,
+                    file_list_sources,
                     &self.include_directories,
                     &self.dpi_functions,
                     name,
                     ports,
                     &local_artifacts_directory,
+                    self.options
+                        .cache_directory
+                        .as_ref()
+                        .map(|cache_directory| {
+                            cache_directory.join(&local_directory_name)
+                        })
+                        .as_deref(),
                     &self.options,
                     config,
                     self.options.log,
@@ -666,6 +1662,22 @@ impl VerilatorRuntime {
                 .whatever_context(
                     "Failed to build verilator dynamic library",
                 )?;
+                self.build_warnings.borrow_mut().extend(warnings);
+
+                let manifest_path = local_artifacts_directory
+                    .join(manifest::MANIFEST_FILE_NAME);
+                if was_rebuilt || !manifest_path.is_file() {
+                    let verilator_version =
+                        build_library::verilator_version(&self.options)?;
+                    manifest::Manifest {
+                        name,
+                        source_path,
+                        config_hash: library_key.hash,
+                        verilator_version: &verilator_version,
+                        library_path: &library_path,
+                    }
+                    .write(&manifest_path)?;
+                }
 
                 if self.options.log {
                     log::info!("Opening the dynamic library");
@@ -682,6 +1694,11 @@ impl VerilatorRuntime {
                     &self.options,
                 )?;
 
+                let library = Arc::new(library);
+                if let Some(shared_library_key) = shared_library_key {
+                    SHARED_LIBRARIES
+                        .insert(shared_library_key, library.clone());
+                }
                 let library_idx = self.library_arena.push(library);
                 entry.insert(library_idx);
 
@@ -712,3 +1729,83 @@ impl VerilatorRuntime {
             .expect("bug: We just inserted the library"))
     }
 }
+
+thread_local! {
+    /// Runtimes created by [`quick_runtime`], keyed by their source files.
+    static QUICK_MODEL_RUNTIMES: RefCell<
+        HashMap<Vec<Utf8PathBuf>, &'static VerilatorRuntime>,
+    > = RefCell::new(HashMap::new());
+}
+
+/// Constructs a model without setting up a [`VerilatorRuntime`] first, which is
+/// handy for scratch tests and examples:
+///
+/// ```no_run
+/// # use marlin_verilator::*;
+/// # struct Main;
+/// # impl<'ctx> AsVerilatedModel<'ctx> for Main {
+/// #     fn name() -> &'static str { "main" }
+/// #     fn source_path() -> &'static str { "src/main.sv" }
+/// #     fn ports() -> &'static [(&'static str, usize, usize, PortDirection)] { &[] }
+/// #     fn port_descriptors() -> &'static [PortDescriptor] { &[] }
+/// #     fn init_from(_: &'ctx VerilatorRuntime, _: &'ctx libloading::Library, _: bool) -> Result<Self, snafu::Whatever> { Ok(Main) }
+/// #     unsafe fn model(&self) -> *mut std::ffi::c_void { std::ptr::null_mut() }
+/// # }
+/// let main = quick_model::<Main>(&["src/main.sv"])?;
+/// # Ok::<(), snafu::Whatever>(())
+/// ```
+///
+/// The model is created with the runtime [`quick_runtime`] returns for
+/// `source_files`, so later calls with the same `source_files` reuse it.
+pub fn quick_model<M: AsVerilatedModel<'static>>(
+    source_files: &[&str],
+) -> Result<M, Whatever> {
+    quick_runtime(source_files)?.create_model_simple()
+}
+
+/// The runtime [`quick_model`] creates models with for `source_files`.
+///
+/// The first call with a given set of `source_files` creates a runtime with
+/// default options whose artifacts live in a `marlin-quick-model` directory
+/// under the system temporary directory. Later calls with the same
+/// `source_files` on the same thread reuse it, so its models are only built
+/// once. Since [`VerilatorRuntime`] is not thread-safe, each thread gets its
+/// own runtimes. They share the artifact directory with each other and with
+/// other processes, and thus built libraries, which is safe because building
+/// locks the artifact directory.
+///
+/// Every such runtime is intentionally leaked so that models can borrow it for
+/// `'static`: it lives for the rest of the process, along with its loaded
+/// libraries.
+pub fn quick_runtime(
+    source_files: &[&str],
+) -> Result<&'static VerilatorRuntime, Whatever> {
+    let key = source_files
+        .iter()
+        .map(Utf8PathBuf::from)
+        .collect::<Vec<_>>();
+    QUICK_MODEL_RUNTIMES.with_borrow_mut(|runtimes| match runtimes.entry(key) {
+        Entry::Occupied(entry) => Ok(*entry.get()),
+        Entry::Vacant(entry) => {
+            let artifact_directory = Utf8PathBuf::try_from(
+                std::env::temp_dir().join("marlin-quick-model"),
+            )
+            .whatever_context(
+                "System temporary directory is not a UTF-8 path",
+            )?;
+            let source_files = entry
+                .key()
+                .iter()
+                .map(Utf8PathBuf::as_path)
+                .collect::<Vec<_>>();
+            let runtime = VerilatorRuntime::new(
+                &artifact_directory,
+                &source_files,
+                &[],
+                [],
+                VerilatorRuntimeOptions::default(),
+            )?;
+            Ok(*entry.insert(Box::leak(Box::new(runtime))))
+        }
+    })
+}
@@ -0,0 +1,80 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Machine-readable descriptions of built libraries. Every library built by a
+//! [`crate::VerilatorRuntime`] lives in its own subdirectory of the artifact
+//! directory alongside a `manifest.json` like:
+//!
+//! ```json
+//! {
+//!   "name": "main",
+//!   "source_path": "src/main.sv",
+//!   "config_hash": 1234567890,
+//!   "verilator_version": "Verilator 5.030 2024-10-27",
+//!   "library_path": "artifacts/main_src_main.sv_1234567890/obj_dir/libmarlin_Vmain.so"
+//! }
+//! ```
+//!
+//! The `config_hash` covers the model's ports and
+//! [`crate::VerilatedModelConfig`] and also suffixes the subdirectory name. The
+//! manifest is rewritten whenever the library is rebuilt, so its modification
+//! time matches the library's. Since each subdirectory is locked while its
+//! library is built, tools can list what is cached by reading
+//! `*/manifest.json` in the artifact directory.
+
+use std::{fmt::Write, fs};
+
+use camino::Utf8Path;
+use snafu::{ResultExt, Whatever};
+
+/// The name of the manifest file in each library's artifact subdirectory.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+pub(crate) struct Manifest<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) source_path: &'a str,
+    pub(crate) config_hash: u64,
+    pub(crate) verilator_version: &'a str,
+    pub(crate) library_path: &'a Utf8Path,
+}
+
+impl Manifest<'_> {
+    pub(crate) fn write(&self, path: &Utf8Path) -> Result<(), Whatever> {
+        let mut json = String::new();
+        writeln!(
+            &mut json,
+            "{{\n  \"name\": {},\n  \"source_path\": {},\n  \"config_hash\": {},\n  \"verilator_version\": {},\n  \"library_path\": {}\n}}",
+            json_string(self.name),
+            json_string(self.source_path),
+            self.config_hash,
+            json_string(self.verilator_version),
+            json_string(self.library_path.as_str())
+        )
+        .whatever_context("Failed to format manifest")?;
+
+        fs::write(path, json)
+            .whatever_context(format!("Failed to write manifest to {path}"))
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::from('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                json.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
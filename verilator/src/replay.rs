@@ -0,0 +1,288 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Replaying a recorded VCD as stimulus, e.g., to reproduce a failing
+//! waveform captured from another simulator against fixed RTL.
+//!
+//! ```no_run
+//! # use marlin_verilator::*;
+//! # use marlin_verilator::replay::*;
+//! # use snafu::ResultExt;
+//! # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+//! # || -> Result<(), snafu::Whatever> {
+//! # let mut model = runtime.create_dyn_model("main", "src/main.sv", &[], VerilatedModelConfig::default())?;
+//! replay_vcd(&mut model, "failing.vcd", true)
+//!     .whatever_context("Replay diverged from the recording")?;
+//! # Ok(()) };
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use snafu::{ResultExt, Snafu};
+
+use crate::{
+    AsDynamicVerilatedModel, PortDirection,
+    dynamic::{
+        DynamicVerilatedModel, DynamicVerilatedModelError, VerilatorValue,
+    },
+    types,
+};
+
+/// The reason [`replay_vcd`] stopped.
+#[derive(Debug, Snafu)]
+pub enum ReplayError {
+    #[snafu(display("Failed to read VCD {}", path.display()))]
+    Read { path: PathBuf, source: io::Error },
+    #[snafu(display("Malformed VCD: {message}"))]
+    Malformed { message: String },
+    #[snafu(display(
+        "VCD declares none of the ports of verilated module {top_module}"
+    ))]
+    NoPorts { top_module: String },
+    #[snafu(display(
+        "Replay diverged at time {time}: port {port} was recorded as {expected} but is {actual}"
+    ))]
+    Mismatch {
+        time: u64,
+        port: String,
+        expected: VerilatorValue,
+        actual: VerilatorValue,
+    },
+    #[snafu(display("Failed to access a port at time {time}"))]
+    PortAccess {
+        time: u64,
+        source: DynamicVerilatedModelError,
+    },
+}
+
+/// The changes to the model's ports at one timestamp of a VCD.
+struct Step<'a> {
+    time: u64,
+    changes: Vec<(&'a str, VerilatorValue)>,
+}
+
+/// Replays the VCD at `path` into `model`, returning the number of timestamps
+/// replayed. At each timestamp that changes one of the model's ports, the
+/// recorded values of its inputs (including any clocks) are pinned and the
+/// model is evaluated. If `check_outputs`, every output recorded so far must
+/// then read as recorded.
+///
+/// A signal is matched to a port by name in the outermost scope declaring any
+/// of the model's ports, which is the top module in VCDs written by Verilator.
+/// Unknown (`x`) and high-impedance (`z`) bits replay as zero. Values of any
+/// width are supported, including for ports wider than 64 bits.
+pub fn replay_vcd(
+    model: &mut DynamicVerilatedModel<'_>,
+    path: impl AsRef<Path>,
+    check_outputs: bool,
+) -> Result<usize, ReplayError> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path).context(ReadSnafu { path })?;
+    let steps = parse_steps(&contents, model)?;
+
+    let mut recorded_outputs = BTreeMap::new();
+    for Step { time, changes } in &steps {
+        for (port, value) in changes {
+            let (width, direction, _) = model.ports[*port];
+            let value = if width <= 64 {
+                VerilatorValue::masked(value.to_u64(), width)
+            } else {
+                VerilatorValue::masked_words(value.to_words(), width)
+            };
+            if direction == PortDirection::Output {
+                recorded_outputs.insert(*port, value);
+            } else {
+                model
+                    .pin(*port, value)
+                    .context(PortAccessSnafu { time: *time })?;
+            }
+        }
+
+        model.eval();
+
+        if check_outputs {
+            for (port, expected) in &recorded_outputs {
+                let actual = model
+                    .read(*port)
+                    .context(PortAccessSnafu { time: *time })?;
+                if actual != *expected {
+                    return MismatchSnafu {
+                        time: *time,
+                        port: *port,
+                        expected: expected.clone(),
+                        actual,
+                    }
+                    .fail();
+                }
+            }
+        }
+    }
+
+    Ok(steps.len())
+}
+
+/// Splits the value changes in the VCD `contents` into the timestamps at which
+/// any port of `model` changes.
+fn parse_steps<'a>(
+    contents: &'a str,
+    model: &DynamicVerilatedModel<'_>,
+) -> Result<Vec<Step<'a>>, ReplayError> {
+    let mut tokens = contents.split_whitespace();
+
+    let mut scope = vec![];
+    // (scope depth, identifier code, name) of each variable naming a port
+    let mut variables = vec![];
+    while let Some(token) = tokens.next() {
+        match token {
+            "$scope" => {
+                let (_kind, name) = (next(&mut tokens)?, next(&mut tokens)?);
+                scope.push(name);
+                skip_to_end(&mut tokens)?;
+            }
+            "$upscope" => {
+                scope.pop();
+                skip_to_end(&mut tokens)?;
+            }
+            "$var" => {
+                let (_kind, _size) = (next(&mut tokens)?, next(&mut tokens)?);
+                let (code, name) = (next(&mut tokens)?, next(&mut tokens)?);
+                if model.ports.contains_key(name) {
+                    variables.push((scope.len(), code, name));
+                }
+                skip_to_end(&mut tokens)?;
+            }
+            "$enddefinitions" => {
+                skip_to_end(&mut tokens)?;
+                break;
+            }
+            _ if token.starts_with('$') => skip_to_end(&mut tokens)?,
+            _ => {
+                return MalformedSnafu {
+                    message: format!(
+                        "unexpected `{token}` among the declarations"
+                    ),
+                }
+                .fail();
+            }
+        }
+    }
+
+    let Some(top_depth) = variables.iter().map(|(depth, _, _)| *depth).min()
+    else {
+        return NoPortsSnafu {
+            top_module: model.name.clone(),
+        }
+        .fail();
+    };
+    // ports like an output assigned directly from an input may share a code
+    let mut ports_by_code: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (depth, code, name) in variables {
+        if depth == top_depth {
+            ports_by_code.entry(code).or_default().push(name);
+        }
+    }
+
+    let mut steps = vec![];
+    let mut step = Step {
+        time: 0,
+        changes: vec![],
+    };
+    while let Some(token) = tokens.next() {
+        let (value, code) = if let Some(time) = token.strip_prefix('#') {
+            let time = time.parse().map_err(|_| ReplayError::Malformed {
+                message: format!("invalid timestamp `{token}`"),
+            })?;
+            let next_step = Step {
+                time,
+                changes: vec![],
+            };
+            let step = std::mem::replace(&mut step, next_step);
+            if !step.changes.is_empty() {
+                steps.push(step);
+            }
+            continue;
+        } else if matches!(
+            token,
+            "$dumpvars" | "$dumpall" | "$dumpon" | "$dumpoff" | "$end"
+        ) {
+            continue;
+        } else if token.starts_with('$') {
+            skip_to_end(&mut tokens)?;
+            continue;
+        } else if let Some(bits) =
+            token.strip_prefix('b').or_else(|| token.strip_prefix('B'))
+        {
+            (parse_bits(bits)?, next(&mut tokens)?)
+        } else if token.starts_with(['r', 'R']) {
+            // real-valued variables cannot be ports of a model
+            next(&mut tokens)?;
+            continue;
+        } else if let Some(code) =
+            token.strip_prefix(['0', '1', 'x', 'X', 'z', 'Z'])
+        {
+            let bit = token.starts_with('1') as types::WData;
+            (VerilatorValue::WData(vec![bit]), code)
+        } else {
+            return MalformedSnafu {
+                message: format!(
+                    "unexpected `{token}` among the value changes"
+                ),
+            }
+            .fail();
+        };
+
+        if let Some(ports) = ports_by_code.get(code) {
+            step.changes
+                .extend(ports.iter().map(|port| (*port, value.clone())));
+        }
+    }
+    if !step.changes.is_empty() {
+        steps.push(step);
+    }
+
+    Ok(steps)
+}
+
+fn next<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+) -> Result<&'a str, ReplayError> {
+    tokens.next().ok_or_else(|| ReplayError::Malformed {
+        message: "unexpected end of file".into(),
+    })
+}
+
+fn skip_to_end<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+) -> Result<(), ReplayError> {
+    while next(tokens)? != "$end" {}
+    Ok(())
+}
+
+/// Parses the digits of a binary vector value of any width, reading `x` and
+/// `z` as zero.
+fn parse_bits(bits: &str) -> Result<VerilatorValue, ReplayError> {
+    let bits = bits.chars().collect::<Vec<_>>();
+    // the last digit is the least significant bit
+    let words = bits
+        .rchunks(32)
+        .map(|word| {
+            word.iter()
+                .try_fold(0, |value: types::WData, bit| match bit {
+                    '0' | 'x' | 'X' | 'z' | 'Z' => Ok(value << 1),
+                    '1' => Ok(value << 1 | 1),
+                    _ => MalformedSnafu {
+                        message: format!("invalid bit `{bit}` in vector value"),
+                    }
+                    .fail(),
+                })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(VerilatorValue::WData(words))
+}
@@ -0,0 +1,156 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Switching activity interchange format (SAIF) output for power estimation,
+//! derived from Verilator's toggle coverage. Models built with
+//! [`crate::coverage::CoverageKinds::toggle`] coverage can write a SAIF file
+//! with `write_saif`.
+//!
+//! Verilator only counts toggles, so each net in the file records its toggle
+//! count (`TC`) but not the time it spent at each level (`T0`/`T1`).
+
+use std::{collections::BTreeMap, ffi, fmt::Write, fs, path::Path};
+
+use libloading::Library;
+use snafu::{ResultExt, Whatever, whatever};
+
+use crate::coverage::write_coverage_from_library;
+
+/// The nets and child instances of one level of the design hierarchy.
+#[derive(Default)]
+struct Instance {
+    nets: BTreeMap<String, u64>,
+    children: BTreeMap<String, Instance>,
+}
+
+#[doc(hidden)]
+pub fn write_saif_from_library(
+    library: &Library,
+    top_module: &str,
+    model: *mut ffi::c_void,
+    path: &Path,
+    duration_ps: u64,
+) -> Result<(), Whatever> {
+    let coverage_path = path.with_extension("coverage.dat");
+    write_coverage_from_library(library, top_module, model, &coverage_path)?;
+
+    let coverage =
+        fs::read_to_string(&coverage_path).whatever_context(format!(
+            "Failed to read coverage data written to {}",
+            coverage_path.display()
+        ))?;
+    let _ = fs::remove_file(&coverage_path);
+
+    fs::write(path, coverage_to_saif(&coverage, duration_ps)?).whatever_context(
+        format!("Failed to write SAIF file to {}", path.display()),
+    )
+}
+
+/// Converts Verilator coverage data (the contents of a `coverage.dat`) into a
+/// SAIF file spanning `duration_ps` picoseconds, using only the toggle
+/// coverage points.
+fn coverage_to_saif(
+    coverage: &str,
+    duration_ps: u64,
+) -> Result<String, Whatever> {
+    let mut top = Instance::default();
+
+    for line in coverage.lines() {
+        // coverage points look like `C '<fields>' <count>`, where each field
+        // is `\x01<key>\x02<value>`
+        let Some(rest) = line.strip_prefix("C '") else {
+            continue;
+        };
+        let Some((fields, count)) = rest.rsplit_once("' ") else {
+            whatever!("Malformed coverage point: {line}");
+        };
+        let count: u64 = count
+            .trim()
+            .parse()
+            .whatever_context(format!("Malformed coverage count: {line}"))?;
+
+        let mut page = None;
+        let mut hierarchy = None;
+        let mut comment = None;
+        for field in fields.split('\x01').filter(|field| !field.is_empty()) {
+            let Some((key, value)) = field.split_once('\x02') else {
+                whatever!("Malformed coverage field: {line}");
+            };
+            match key {
+                "page" => page = Some(value),
+                "h" => hierarchy = Some(value),
+                "o" => comment = Some(value),
+                _ => {}
+            }
+        }
+
+        if !page.is_some_and(|page| page.starts_with("v_toggle")) {
+            continue;
+        }
+        let (Some(hierarchy), Some(comment)) = (hierarchy, comment) else {
+            whatever!("Toggle coverage point is missing its signal: {line}");
+        };
+
+        // newer Verilator versions count rising and falling toggles
+        // separately as `<signal>:0->1` and `<signal>:1->0`
+        let net = comment
+            .split_once(':')
+            .map_or(comment, |(net, _)| net)
+            .to_string();
+
+        let mut instance = &mut top;
+        for name in hierarchy.split('.') {
+            instance = instance.children.entry(name.to_string()).or_default();
+        }
+        *instance.nets.entry(net).or_default() += count;
+    }
+
+    let mut saif = String::new();
+    writeln!(
+        &mut saif,
+        "(SAIFILE\n(SAIFVERSION \"2.0\")\n(DIRECTION \"backward\")\n(DESIGN )\n(VENDOR \"marlin\")\n(PROGRAM_NAME \"marlin\")\n(DIVIDER / )\n(TIMESCALE 1 ps)\n(DURATION {duration_ps})"
+    )
+    .whatever_context("Failed to format SAIF header")?;
+    for (name, instance) in &top.children {
+        write_instance(&mut saif, name, instance, 0)
+            .whatever_context("Failed to format SAIF instance")?;
+    }
+    saif.push_str(")\n");
+
+    Ok(saif)
+}
+
+fn write_instance(
+    saif: &mut String,
+    name: &str,
+    instance: &Instance,
+    depth: usize,
+) -> std::fmt::Result {
+    let indent = "  ".repeat(depth + 1);
+    writeln!(saif, "{indent}(INSTANCE {}", escape(name))?;
+    if !instance.nets.is_empty() {
+        writeln!(saif, "{indent}  (NET")?;
+        for (net, toggles) in &instance.nets {
+            writeln!(saif, "{indent}    ({} (TC {toggles}))", escape(net))?;
+        }
+        writeln!(saif, "{indent}  )")?;
+    }
+    for (child_name, child) in &instance.children {
+        write_instance(saif, child_name, child, depth + 1)?;
+    }
+    writeln!(saif, "{indent})")
+}
+
+/// SAIF identifiers escape special characters like bit-select brackets.
+fn escape(identifier: &str) -> String {
+    identifier
+        .chars()
+        .flat_map(|c| {
+            let escaped = !(c.is_ascii_alphanumeric() || c == '_');
+            escaped.then_some('\\').into_iter().chain([c])
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+// Copyright (C) 2024 Ethan Uppal.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A testbench driver bundling a model with its clock, the simulation time,
+//! and optionally a VCD trace.
+//!
+//! ```no_run
+//! # use marlin_verilator::*;
+//! # use marlin_verilator::simulation::*;
+//! # use snafu::ResultExt;
+//! # let runtime = VerilatorRuntime::new("".as_ref(), &[], &[], [], Default::default()).unwrap();
+//! # || -> Result<(), snafu::Whatever> {
+//! # let mut model = runtime.create_dyn_model("main", "src/main.sv", &[], VerilatedModelConfig::default())?;
+//! let mut simulation = Simulation::new(&mut model, "clk", 1_000)?;
+//! simulation.poke("start", 1u8).whatever_context("Failed to start")?;
+//! simulation.run(10).whatever_context("Failed to simulate")?;
+//! let done = simulation.peek("done").whatever_context("Failed to read")?;
+//! # Ok(()) };
+//! ```
+
+use std::ffi;
+
+use libloading::Library;
+use snafu::{ResultExt, Snafu, Whatever};
+
+use crate::{
+    AsDynamicVerilatedModel,
+    clock::{ClockScheduler, ClockSpec},
+    dynamic::{DynamicVerilatedModelError, VerilatorValue},
+    vcd::{self, Vcd},
+};
+
+/// Drives a model with a single clock, keeping track of the simulation time
+/// and dumping every evaluation to a VCD trace, if any, at that time.
+///
+/// The clock starts low at time zero and each [`Simulation::tick`] spans one
+/// clock period containing one rising edge, at its midpoint.
+pub struct Simulation<'model, M> {
+    vcd: Vcd<'model, M>,
+    scheduler: ClockScheduler,
+    period_ps: u64,
+}
+
+impl<'model, 'ctx, M: AsDynamicVerilatedModel<'ctx>> Simulation<'model, M> {
+    /// Creates an untraced simulation of `model` whose clock drives the input
+    /// `clock_port` with a period of `period_ps` picoseconds. Fails if the
+    /// period is not nonzero and even.
+    pub fn new(
+        model: &'model mut M,
+        clock_port: impl Into<String>,
+        period_ps: u64,
+    ) -> Result<Self, Whatever> {
+        Self::traced(
+            vcd::__private::new_vcd_useless(model),
+            clock_port,
+            period_ps,
+        )
+    }
+
+    /// Like [`Simulation::new`], but simulates the model traced by `vcd`,
+    /// dumping it after every evaluation.
+    pub fn traced(
+        vcd: Vcd<'model, M>,
+        clock_port: impl Into<String>,
+        period_ps: u64,
+    ) -> Result<Self, Whatever> {
+        let scheduler = ClockScheduler::new([ClockSpec {
+            port: clock_port.into(),
+            period_ps,
+            phase_ps: period_ps / 2,
+        }])?;
+        Ok(Self {
+            vcd,
+            scheduler,
+            period_ps,
+        })
+    }
+
+    /// The current simulation time in picoseconds.
+    pub fn time_ps(&self) -> u64 {
+        self.scheduler.time_ps()
+    }
+
+    /// Advances the simulation by one clock period.
+    pub fn tick(&mut self) -> Result<(), DynamicVerilatedModelError> {
+        self.scheduler.advance_traced(&mut self.vcd, self.period_ps)
+    }
+
+    /// Advances the simulation by `cycles` clock periods.
+    pub fn run(
+        &mut self,
+        cycles: u64,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        for _ in 0..cycles {
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    /// Sets the input `port` to `value`, which takes effect at the next clock
+    /// edge.
+    pub fn poke(
+        &mut self,
+        port: impl Into<String>,
+        value: impl Into<VerilatorValue>,
+    ) -> Result<(), DynamicVerilatedModelError> {
+        self.vcd.pin(port, value)
+    }
+
+    /// Reads the current value of the output `port`.
+    pub fn peek(
+        &self,
+        port: impl Into<String>,
+    ) -> Result<VerilatorValue, DynamicVerilatedModelError> {
+        self.vcd.read(port)
+    }
+
+    /// The simulated model, e.g., to access generated fields directly.
+    pub fn model(&mut self) -> &mut M {
+        &mut self.vcd
+    }
+
+    /// Ends the simulation, returning the trace (so that it can be closed or
+    /// continued) and thereby the model.
+    pub fn finish(self) -> Vcd<'model, M> {
+        self.vcd
+    }
+}
+
+/// The ways a simulation can end badly, as reported when it is finished with
+/// `finish` on a model.
+#[derive(Debug, Snafu)]
+pub enum SimulationError {
+    #[snafu(display(
+        "Simulation of verilated module {top_module} ended with an error, e.g., from a failed assertion or `$stop`"
+    ))]
+    EndedWithError { top_module: String },
+    #[snafu(display(
+        "Failed to load symbol `{symbol}` of verilated module {top_module} from its library, which may be stale"
+    ))]
+    MissingSymbol {
+        top_module: String,
+        symbol: String,
+        source: libloading::Error,
+    },
+}
+
+/// Runs the `final` blocks of `model`, reporting whether the simulation ended
+/// with an error. Errors raised in the `final` blocks themselves are reported
+/// rather than aborting the process.
+#[doc(hidden)]
+pub fn finish_model(
+    library: &Library,
+    top_module: &str,
+    model: *mut ffi::c_void,
+) -> Result<(), SimulationError> {
+    let symbol = format!("ffi_V{top_module}_finish");
+    let finish: libloading::Symbol<extern "C" fn(*mut ffi::c_void) -> bool> =
+        unsafe { library.get(symbol.as_bytes()) }.context(
+            MissingSymbolSnafu {
+                top_module,
+                symbol: &symbol,
+            },
+        )?;
+    if (*finish)(model) {
+        return Err(SimulationError::EndedWithError {
+            top_module: top_module.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// The exit status implied by how the simulation of `model` ended, or `None`
+/// if it has not ended, given the `ffi_V{top_module}_finish_code` function
+/// loaded from its library.
+#[doc(hidden)]
+pub fn finish_code(
+    finish_code: extern "C" fn(*mut ffi::c_void) -> ffi::c_int,
+    model: *mut ffi::c_void,
+) -> Option<i32> {
+    let code = finish_code(model);
+    (code >= 0).then_some(code)
+}
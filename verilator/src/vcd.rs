@@ -4,11 +4,18 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::marker::PhantomData;
+use std::{
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
 
 #[doc(hidden)]
 pub mod __private {
-    use std::{ffi, marker::PhantomData};
+    use std::{
+        cell::Cell,
+        ffi,
+        rc::{Rc, Weak},
+    };
 
     use super::Vcd;
 
@@ -18,11 +25,26 @@ pub mod __private {
         pub(crate) open_next: extern "C" fn(*mut ffi::c_void, bool),
         pub(crate) flush: extern "C" fn(*mut ffi::c_void),
         close_and_delete: extern "C" fn(*mut ffi::c_void),
+        closed: Cell<bool>,
+    }
+
+    impl VcdImpl {
+        /// The trace handle, or `None` if it has already been closed.
+        pub(crate) fn open_handle(&self) -> Option<*mut ffi::c_void> {
+            (!self.closed.get()).then_some(self.handle)
+        }
+
+        fn close(&self) {
+            if let Some(handle) = self.open_handle() {
+                (self.close_and_delete)(handle);
+                self.closed.set(true);
+            }
+        }
     }
 
     impl Drop for VcdImpl {
         fn drop(&mut self) {
-            (self.close_and_delete)(self.handle);
+            self.close();
         }
     }
     #[derive(Clone, Copy)]
@@ -37,40 +59,85 @@ pub mod __private {
         pub close_and_delete: extern "C" fn(*mut ffi::c_void),
     }
 
-    pub fn new_vcd<'ctx>(
+    /// A non-owning reference to an open [`Vcd`], which lets a model flush the
+    /// trace without keeping it open.
+    pub struct WeakVcd(Weak<VcdImpl>);
+
+    impl WeakVcd {
+        /// Flushes the referenced trace if it is still open.
+        pub fn flush(&self) {
+            if let Some(inner) = self.0.upgrade() {
+                if let Some(handle) = inner.open_handle() {
+                    (inner.flush)(handle);
+                }
+            }
+        }
+
+        /// Closes the referenced trace if it is still open, after which the
+        /// [`Vcd`] ignores all operations. Models call this when they are
+        /// dropped, since the trace cannot outlive the model it records.
+        pub fn close(&self) {
+            if let Some(inner) = self.0.upgrade() {
+                inner.close();
+            }
+        }
+    }
+
+    pub fn downgrade_vcd<M>(vcd: &Vcd<'_, M>) -> WeakVcd {
+        WeakVcd(vcd.inner.as_ref().map(Rc::downgrade).unwrap_or_default())
+    }
+
+    pub fn new_vcd<M>(
+        model: &mut M,
         handle: *mut ffi::c_void,
         dump: extern "C" fn(*mut ffi::c_void, u64),
         open_next: extern "C" fn(*mut ffi::c_void, bool),
         flush: extern "C" fn(*mut ffi::c_void),
         close_and_delete: extern "C" fn(*mut ffi::c_void),
-    ) -> Vcd<'ctx> {
+    ) -> Vcd<'_, M> {
         Vcd {
-            inner: Some(VcdImpl {
+            model,
+            inner: Some(Rc::new(VcdImpl {
                 handle,
                 dump,
                 open_next,
                 flush,
                 close_and_delete,
-            }),
-            _marker: std::marker::PhantomData,
+                closed: Cell::new(false),
+            })),
         }
     }
 
-    pub fn new_vcd_useless<'ctx>() -> Vcd<'ctx> {
-        Vcd {
-            inner: None,
-            _marker: PhantomData,
-        }
+    pub fn new_vcd_useless<M>(model: &mut M) -> Vcd<'_, M> {
+        Vcd { model, inner: None }
     }
 }
 
-/// A VCD dump.
-pub struct Vcd<'ctx> {
-    inner: Option<__private::VcdImpl>,
-    _marker: PhantomData<&'ctx ()>,
+/// A VCD dump of the model `M`.
+///
+/// The dump mutably borrows the model it records for as long as it is open, so
+/// the borrow checker prevents the model from being dropped while tracing. The
+/// model remains accessible through the dump, which dereferences to it.
+pub struct Vcd<'model, M> {
+    model: &'model mut M,
+    inner: Option<Rc<__private::VcdImpl>>,
+}
+
+impl<M> Deref for Vcd<'_, M> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        self.model
+    }
+}
+
+impl<M> DerefMut for Vcd<'_, M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.model
+    }
 }
 
-impl Vcd<'_> {
+impl<M> Vcd<'_, M> {
     /// Documentation taken from the Verilator header file:
     ///
     /// > Write one cycle of dump data
@@ -78,7 +145,9 @@ impl Vcd<'_> {
     /// > e.g. `->dump(contextp->time())`.
     pub fn dump(&mut self, timestamp: u64) {
         if let Some(inner) = &self.inner {
-            (inner.dump)(inner.handle, timestamp);
+            if let Some(handle) = inner.open_handle() {
+                (inner.dump)(handle, timestamp);
+            }
         }
     }
 
@@ -89,7 +158,9 @@ impl Vcd<'_> {
     /// > "cat" to be used to combine the header plus any number of data files.
     pub fn open_next(&mut self, increment_filename: bool) {
         if let Some(inner) = &self.inner {
-            (inner.open_next)(inner.handle, increment_filename);
+            if let Some(handle) = inner.open_handle() {
+                (inner.open_next)(handle, increment_filename);
+            }
         }
     }
 
@@ -98,12 +169,14 @@ impl Vcd<'_> {
     /// > Flush dump
     pub fn flush(&mut self) {
         if let Some(inner) = &self.inner {
-            (inner.flush)(inner.handle);
+            if let Some(handle) = inner.open_handle() {
+                (inner.flush)(handle);
+            }
         }
     }
 
     /// The VCD is automatically closed when dropped, but it may be useful to
-    /// call this manually.
+    /// call this manually, which also ends the borrow of the model.
     pub fn close(self) {
         drop(self.inner);
     }